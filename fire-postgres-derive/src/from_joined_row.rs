@@ -0,0 +1,70 @@
+use ::quote::{quote, ToTokens};
+
+use syn::{DeriveInput, Error, Fields};
+
+use proc_macro2::TokenStream;
+
+type Result<T> = std::result::Result<T, Error>;
+
+macro_rules! err {
+	($input:expr, $msg:expr) => {
+		Error::new_spanned($input.into_token_stream(), $msg)
+	};
+}
+
+pub fn expand_from_joined_row(
+	input: &DeriveInput,
+	name: &TokenStream,
+) -> Result<proc_macro::TokenStream> {
+	let syn::Data::Struct(data) = &input.data else {
+		return Err(err!(input, "is not supported"));
+	};
+
+	let Fields::Named(fields) = &data.fields else {
+		return Err(err!(&data.fields, "only named fields are supported"));
+	};
+
+	let (impl_gens, ty_gens, where_clause) = input.generics.split_for_impl();
+	let ident = &input.ident;
+	let row = quote!(#name::row);
+
+	let mut tables = quote!();
+	let mut from_parts = quote!();
+
+	// every field is one table, in the order of the joins
+	for field in fields.named.iter() {
+		let field_ident = &field.ident;
+		let ty = &field.ty;
+
+		tables.extend(quote!(
+			<#ty as #row::JoinPart>::select_columns(),
+		));
+		from_parts.extend(quote!(
+			#field_ident: {
+				let (row, matched) =
+					parts.next().ok_or("missing part of joined row")?;
+				<#ty as #row::JoinPart>::from_part(row, matched)?
+			},
+		));
+	}
+
+	let toks = quote!(
+		impl #impl_gens #row::FromJoinedRow for #ident #ty_gens #where_clause {
+			fn tables() -> std::vec::Vec<&'static str> {
+				std::vec![#tables]
+			}
+
+			fn from_parts(
+				parts: std::vec::Vec<(#row::Row, bool)>
+			) -> std::result::Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+				let mut parts = parts.into_iter();
+
+				Ok(Self {
+					#from_parts
+				})
+			}
+		}
+	);
+
+	Ok(toks.into())
+}
@@ -2,11 +2,11 @@ use std::fmt::Write;
 
 use ::quote::{quote, ToTokens};
 
-use syn::{DeriveInput, Error};
+use syn::{Attribute, DeriveInput, Error};
 use syn::{Fields, FieldsNamed, FieldsUnnamed};
 use syn::{Lifetime, LifetimeParam};
 
-use proc_macro2::{Span, TokenStream};
+use proc_macro2::{Ident, Span, TokenStream};
 
 type Result<T> = std::result::Result<T, Error>;
 
@@ -55,6 +55,11 @@ pub fn expand_from_row(
 				Fields::Named(fields) => {
 					let (select_columns, from_named_fields) =
 						parse_named_fields(fields)?;
+					let columns = if has_columns(&input.attrs)? {
+						named_columns(fields, name)
+					} else {
+						quote!()
+					};
 
 					let row = quote!(#name::row);
 					let toks = quote!(
@@ -73,6 +78,10 @@ pub fn expand_from_row(
 								#select_columns
 							}
 						}
+
+						impl #input_impl_gens #ident #ty_gens #where_clause {
+							#columns
+						}
 					);
 
 					Ok(toks.into())
@@ -125,6 +134,52 @@ fn parse_named_fields(
 	Ok((quote!(#select_columns), from_stream))
 }
 
+/// Returns true if the struct asks for the column constants with
+/// `#[row(columns)]`.
+pub fn has_columns(attrs: &[Attribute]) -> Result<bool> {
+	let mut columns = false;
+
+	for attr in attrs.iter().filter(|attr| attr.path().is_ident("row")) {
+		attr.parse_nested_meta(|meta| {
+			if meta.path.is_ident("columns") {
+				columns = true;
+				Ok(())
+			} else {
+				Err(meta.error("unsupported row attribute"))
+			}
+		})?;
+	}
+
+	Ok(columns)
+}
+
+/// Creates an associated constant for every field, `email` becomes
+/// `EMAIL: Col<String>`.
+///
+/// The column keeps the `r#` prefix like in `select_columns`, only the name
+/// of the constant drops it.
+fn named_columns(fields: &FieldsNamed, name: &TokenStream) -> TokenStream {
+	let mut stream = quote!();
+
+	for field in fields.named.iter() {
+		let ident = field.ident.as_ref().unwrap();
+		let column = ident.to_string();
+		let const_name = column.trim_start_matches("r#").to_uppercase();
+		let const_ident = Ident::new(&const_name, ident.span());
+		let doc = format!("The column `{column}`.");
+		let vis = &field.vis;
+		let ty = &field.ty;
+
+		stream.extend(quote!(
+			#[doc = #doc]
+			#vis const #const_ident: #name::filter::Col<#ty> =
+				#name::filter::Col::new(#column);
+		));
+	}
+
+	stream
+}
+
 fn parse_unnamed_fields(fields: &FieldsUnnamed) -> Result<TokenStream> {
 	let mut from_stream = quote!();
 
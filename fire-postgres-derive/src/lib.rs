@@ -1,14 +1,18 @@
+mod from_joined_row;
 mod from_row;
 mod row;
 mod table_templ;
+mod test;
 mod to_row;
 
 use ::quote::quote;
+use from_joined_row::expand_from_joined_row;
 use from_row::expand_from_row;
 
 use row::expand_row;
 use syn::parse_macro_input;
 use syn::DeriveInput;
+use syn::ItemFn;
 
 use proc_macro::TokenStream as V1TokenStream;
 
@@ -19,10 +23,14 @@ use table_templ::expand_table_templ;
 use to_row::expand_to_row;
 
 use crate::row::RowInput;
+use crate::test::{expand_test, TestArgs};
 
 // inspired from https://github.com/serde-rs/serde/blob/master/serde_derive
 
-#[proc_macro_derive(TableTempl, attributes(len, index, unique))]
+#[proc_macro_derive(
+	TableTempl,
+	attributes(len, index, unique, text_search, row)
+)]
 pub fn derive_table_templ(input: V1TokenStream) -> V1TokenStream {
 	let input = parse_macro_input!(input as DeriveInput);
 
@@ -40,8 +48,7 @@ pub fn derive_table_templ(input: V1TokenStream) -> V1TokenStream {
 	expand_table_templ(&input, &name).unwrap_or_else(to_compile_error)
 }
 
-// attributes(len, index, unique)
-#[proc_macro_derive(FromRow)]
+#[proc_macro_derive(FromRow, attributes(row))]
 pub fn derive_from_row(input: V1TokenStream) -> V1TokenStream {
 	let input = parse_macro_input!(input as DeriveInput);
 
@@ -59,6 +66,24 @@ pub fn derive_from_row(input: V1TokenStream) -> V1TokenStream {
 	expand_from_row(&input, &name).unwrap_or_else(to_compile_error)
 }
 
+#[proc_macro_derive(FromJoinedRow)]
+pub fn derive_from_joined_row(input: V1TokenStream) -> V1TokenStream {
+	let input = parse_macro_input!(input as DeriveInput);
+
+	// crate name
+	let name =
+		crate_name("fire-postgres").expect("fire-postgres not in dependencies");
+	let name = match name {
+		FoundCrate::Itself => quote!(crate),
+		FoundCrate::Name(n) => {
+			let ident = Ident::new(&n, Span::call_site());
+			quote!(#ident)
+		}
+	};
+
+	expand_from_joined_row(&input, &name).unwrap_or_else(to_compile_error)
+}
+
 // attributes(len, index, unique)
 #[proc_macro_derive(ToRow)]
 pub fn derive_to_row(input: V1TokenStream) -> V1TokenStream {
@@ -96,6 +121,28 @@ pub fn row(input: V1TokenStream) -> V1TokenStream {
 	expand_row(&input, &name).unwrap_or_else(to_compile_error)
 }
 
+/// Runs an async test with its own database, see `fire_postgres::testing`.
+#[proc_macro_attribute]
+pub fn test(attr: V1TokenStream, item: V1TokenStream) -> V1TokenStream {
+	let args = parse_macro_input!(attr as TestArgs);
+	let item = parse_macro_input!(item as ItemFn);
+
+	// crate name
+	let name =
+		crate_name("fire-postgres").expect("fire-postgres not in dependencies");
+	let name = match name {
+		FoundCrate::Itself => quote!(crate),
+		FoundCrate::Name(n) => {
+			let ident = Ident::new(&n, Span::call_site());
+			quote!(#ident)
+		}
+	};
+
+	expand_test(&args, &item, &name)
+		.map(Into::into)
+		.unwrap_or_else(to_compile_error)
+}
+
 fn to_compile_error(error: syn::Error) -> V1TokenStream {
 	let compile_error = syn::Error::to_compile_error(&error);
 	quote!(#compile_error).into()
@@ -111,7 +111,7 @@ pub fn expand_row(
 
 	for (i, field) in input.fields.iter().enumerate() {
 		let name = &field.name;
-		let ident = Ident::new(&name, field.name_span);
+		let ident = Ident::new(name, field.name_span);
 		let value = &field.value;
 
 		if !insert_columns.is_empty() {
@@ -3,7 +3,9 @@ use ::quote::{quote, ToTokens};
 use syn::{DeriveInput, Error};
 use syn::{Field, Fields, FieldsNamed, Type};
 
-use proc_macro2::TokenStream;
+use proc_macro2::{Ident, TokenStream};
+
+use crate::from_row::has_columns;
 
 type Result<T> = std::result::Result<T, Error>;
 
@@ -22,15 +24,53 @@ pub fn expand_table_templ(
 			Fields::Named(fields) => {
 				let ident = &input.ident;
 
-				let info_block = parse_named_fields(fields, name)?;
+				let vis = &input.vis;
+
+				let mut info_block = parse_named_fields(fields, name)?;
+				// the constants of the fields come from FromRow
+				let has_columns = has_columns(&input.attrs)?;
+				let mut columns = quote!();
+
+				for attr in &input.attrs {
+					if attr.path().is_ident("text_search") {
+						let (col, col_name) =
+							parse_text_search(attr, fields, name)?;
+						info_block = quote!(#info_block info.push(#col););
+
+						if !has_columns {
+							continue;
+						}
+
+						let const_ident = syn::parse_str::<Ident>(
+							&col_name.value().to_uppercase(),
+						)
+						.map_err(|_| {
+							err!(&col_name, "name needs to be an identifier")
+						})?;
+						let doc = format!(
+							"The text search column `{}`.",
+							col_name.value()
+						);
+						columns.extend(quote!(
+							#[doc = #doc]
+							#vis const #const_ident: #name::filter::Col<
+								#name::filter::TsVector,
+							> = #name::filter::Col::new(#col_name);
+						));
+					}
+				}
 
 				let table = quote!(#name::table);
 				let toks = quote!(
 					impl #table::TableTemplate for #ident {
 						fn table_info() -> #table::Info {
-							{ #info_block }
+							{ #info_block info }
 						}
 					}
+
+					impl #ident {
+						#columns
+					}
 				);
 
 				Ok(toks.into())
@@ -55,11 +95,66 @@ fn parse_named_fields(
 		info_stream.extend(quote!(info.push(#col);));
 	}
 
-	info_stream.extend(quote!(info));
-
 	Ok(info_stream)
 }
 
+/// Parses `#[text_search(name = "search", language = "english",
+/// columns(title, body))]`
+fn parse_text_search(
+	attr: &syn::Attribute,
+	fields: &FieldsNamed,
+	crate_name: &TokenStream,
+) -> Result<(TokenStream, syn::LitStr)> {
+	let mut name: Option<syn::LitStr> = None;
+	let mut language: Option<syn::LitStr> = None;
+	let mut columns: Vec<String> = vec![];
+
+	attr.parse_nested_meta(|meta| {
+		if meta.path.is_ident("name") {
+			name = Some(meta.value()?.parse()?);
+		} else if meta.path.is_ident("language") {
+			language = Some(meta.value()?.parse()?);
+		} else if meta.path.is_ident("columns") {
+			meta.parse_nested_meta(|col| {
+				let ident = col.path.require_ident()?;
+				let exists = fields
+					.named
+					.iter()
+					.any(|f| f.ident.as_ref() == Some(ident));
+				if !exists {
+					return Err(err!(ident, "unknown column"));
+				}
+
+				columns.push(ident.to_string());
+				Ok(())
+			})?;
+		} else {
+			return Err(meta.error("unsupported text_search attribute"));
+		}
+
+		Ok(())
+	})?;
+
+	let name = name.ok_or_else(|| err!(attr, "name is required"))?;
+	if columns.is_empty() {
+		return Err(err!(attr, "at least one column is required"));
+	}
+	let language = language
+		.map(|l| l.value())
+		.unwrap_or_else(|| "simple".into());
+
+	let table = quote!(#crate_name::table);
+	let col = quote!(
+		#table::column::Column::text_search(
+			#name,
+			#language,
+			vec![#(#columns),*]
+		)
+	);
+
+	Ok((col, name))
+}
+
 fn parse_named_field(
 	field: &Field,
 	crate_name: &TokenStream,
@@ -87,6 +182,9 @@ fn parse_named_field(
 					"primary" => quote!(#index_kind::Primary),
 					"unique" => quote!(#index_kind::Unique),
 					"index" => quote!(#index_kind::Index),
+					"gin" => quote!(#index_kind::Gin),
+					"trigram" => quote!(#index_kind::Trigram),
+					"trigram_gist" => quote!(#index_kind::TrigramGist),
 					_ => return Err(err!(res, "not supported index type")),
 				};
 			}
@@ -0,0 +1,146 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{Error, Ident, ItemFn, LitStr, Result, Token};
+
+pub(crate) struct TestArgs {
+	template: Option<LitStr>,
+}
+
+impl Parse for TestArgs {
+	fn parse(input: ParseStream) -> Result<Self> {
+		let mut template = None;
+
+		while !input.is_empty() {
+			let key: Ident = input.parse()?;
+			input.parse::<Token![=]>()?;
+
+			match key.to_string().as_str() {
+				"template" => template = Some(input.parse()?),
+				_ => return Err(Error::new_spanned(key, "unknown argument")),
+			}
+
+			if !input.is_empty() {
+				input.parse::<Token![,]>()?;
+			}
+		}
+
+		Ok(Self { template })
+	}
+}
+
+pub(crate) fn expand_test(
+	args: &TestArgs,
+	item: &ItemFn,
+	name: &TokenStream,
+) -> Result<TokenStream> {
+	let sig = &item.sig;
+
+	if sig.asyncness.is_none() {
+		return Err(Error::new_spanned(
+			sig.fn_token,
+			"the test needs to be async",
+		));
+	}
+
+	if sig.inputs.len() != 1 {
+		return Err(Error::new_spanned(
+			&sig.inputs,
+			"the test needs to take exactly one Database argument",
+		));
+	}
+
+	// the attributes belong to the outer function
+	let mut inner = item.clone();
+	let attrs = std::mem::take(&mut inner.attrs);
+	let vis = &item.vis;
+	let ident = &sig.ident;
+	let output = &sig.output;
+
+	let template = match &args.template {
+		Some(t) => quote!(Some(#t)),
+		None => quote!(None),
+	};
+
+	Ok(quote!(
+		#[::core::prelude::v1::test]
+		#(#attrs)*
+		#vis fn #ident() #output {
+			#inner
+
+			#name::testing::run(#template, #ident)
+		}
+	))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use syn::parse_quote;
+
+	fn expand(args: TokenStream, item: ItemFn) -> Result<String> {
+		let args: TestArgs = syn::parse2(args)?;
+		expand_test(&args, &item, &quote!(fire_postgres)).map(|t| t.to_string())
+	}
+
+	#[test]
+	fn test_expand() {
+		let item: ItemFn = parse_quote!(
+			#[ignore]
+			async fn users(db: Database) {
+				let _ = db;
+			}
+		);
+
+		let expected = quote!(
+			#[::core::prelude::v1::test]
+			#[ignore]
+			fn users() {
+				async fn users(db: Database) {
+					let _ = db;
+				}
+
+				fire_postgres::testing::run(None, users)
+			}
+		);
+		assert_eq!(
+			expand(quote!(), item.clone()).unwrap(),
+			expected.to_string()
+		);
+
+		let expected = quote!(
+			#[::core::prelude::v1::test]
+			#[ignore]
+			fn users() {
+				async fn users(db: Database) {
+					let _ = db;
+				}
+
+				fire_postgres::testing::run(Some("app"), users)
+			}
+		);
+		assert_eq!(
+			expand(quote!(template = "app"), item).unwrap(),
+			expected.to_string()
+		);
+	}
+
+	#[test]
+	fn test_expand_errors() {
+		let not_async: ItemFn = parse_quote!(
+			fn users(db: Database) {}
+		);
+		assert!(expand(quote!(), not_async).is_err());
+
+		let no_db: ItemFn = parse_quote!(
+			async fn users() {}
+		);
+		assert!(expand(quote!(), no_db).is_err());
+
+		let item: ItemFn = parse_quote!(
+			async fn users(db: Database) {}
+		);
+		assert!(expand(quote!(name = "app"), item).is_err());
+	}
+}
@@ -1,3 +1,5 @@
+#![allow(clippy::new_without_default)]
+
 #[cfg(feature = "json")]
 pub mod json;
 pub mod time;
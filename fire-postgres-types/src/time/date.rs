@@ -278,7 +278,7 @@ mod graphql {
 		v: &InputValue<S>,
 	) -> Result<Date, String> {
 		v.as_string_value()
-			.and_then(|s| Date::from_str(s.as_ref()).ok())
+			.and_then(|s| Date::from_str(s).ok())
 			.ok_or_else(|| "Expected a date y-m-d".into())
 	}
 
@@ -273,7 +273,7 @@ mod graphql {
 		v: &InputValue<S>,
 	) -> Result<DateTime, String> {
 		v.as_string_value()
-			.and_then(|s| DateTime::parse_from_iso8601(s.as_ref()).ok())
+			.and_then(|s| DateTime::parse_from_iso8601(s).ok())
 			.ok_or_else(|| "Expected a datetime in iso8601 format".into())
 	}
 
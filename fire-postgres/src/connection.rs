@@ -1,7 +1,10 @@
 // use crate::table::{Table, TableTemplate};
 
 use std::borrow::Borrow;
-use std::fmt::Write;
+use std::collections::HashMap;
+use std::fmt::{self, Write};
+use std::sync::RwLock;
+use std::time::Instant;
 
 use deadpool_postgres::Metrics;
 use deadpool_postgres::{ClientWrapper, Object};
@@ -10,8 +13,10 @@ use futures_util::pin_mut;
 use futures_util::StreamExt;
 use futures_util::TryStreamExt;
 use postgres_types::{BorrowToSql, ToSql, Type};
+use tokio::task::JoinHandle;
 use tokio_postgres::error::SqlState;
 use tokio_postgres::Error as PgError;
+use tokio_postgres::{Client, GenericClient, NoTls};
 
 pub use deadpool::managed::TimeoutType;
 pub use deadpool_postgres::{Config, ConfigError};
@@ -19,16 +24,181 @@ use tokio_postgres::Statement;
 use tokio_postgres::ToStatement;
 use tracing::error;
 
-use crate::filter::Filter;
 use crate::filter::Limit;
-use crate::filter::WhereFilter;
+use crate::filter::{
+	keyset_limit, Aggregates, Cursor, CursorError, Filter, Offset, OffsetPage,
+	Page,
+};
+use crate::filter::{Join, JoinKind, Where, WhereFilter};
+use crate::ident::write_quoted;
+#[cfg(feature = "testing")]
+use crate::mock::MockConnection;
+use crate::row::parse_columns;
 use crate::row::NamedColumns;
 use crate::row::RowStream;
 use crate::row::ToRowStatic;
-use crate::row::{FromRowOwned, ToRow};
+use crate::row::{FromJoinedRow, FromRowOwned, ToRow};
 use crate::try2;
+use crate::Ident;
 use crate::Row;
 
+/// Builds the statement of [`Connection::select`].
+pub(crate) fn select_sql<R>(table: &Ident, filter: impl fmt::Display) -> String
+where
+	R: NamedColumns,
+{
+	format!("SELECT {} FROM {}{}", R::select_columns(), table, filter)
+}
+
+/// Builds the statement of [`Connection::select_columns`], returns an error
+/// if no columns are given.
+pub(crate) fn select_columns_sql<C>(
+	table: &Ident,
+	columns: &[C],
+	filter: impl fmt::Display,
+) -> Result<String, Error>
+where
+	C: AsRef<str>,
+{
+	if columns.is_empty() {
+		return Err(no_columns());
+	}
+
+	let mut sql = String::from("SELECT ");
+	for (i, column) in columns.iter().enumerate() {
+		if i != 0 {
+			sql.push_str(", ");
+		}
+		write_quoted(&mut sql, column.as_ref()).unwrap();
+	}
+	write!(&mut sql, " FROM {}{}", table, filter).unwrap();
+
+	Ok(sql)
+}
+
+pub(crate) fn no_columns() -> Error {
+	Error::Unknown("select_columns needs at least one column".into())
+}
+
+/// Builds the statement of [`Connection::select_join`], returns the number of
+/// columns of every table as well.
+///
+/// The key of every `LEFT JOIN` is selected after the columns of the tables,
+/// it is only null if the join did not match.
+pub(crate) fn join_sql<R>(
+	join: &Join,
+	filter: impl fmt::Display,
+) -> Result<(String, Vec<usize>), Error>
+where
+	R: FromJoinedRow,
+{
+	let tables = R::tables();
+	if tables.len() != join.joins.len() + 1 {
+		return Err(Error::Deserialize(
+			format!(
+				"the row has {} parts but {} tables are joined",
+				tables.len(),
+				join.joins.len() + 1
+			)
+			.into(),
+		));
+	}
+
+	let mut columns = vec![];
+	let mut lens = Vec::with_capacity(tables.len());
+	for (part, alias) in tables.into_iter().zip(join.aliases()) {
+		let names = parse_columns(part);
+		lens.push(names.len());
+
+		columns.extend(names.into_iter().map(|name| {
+			Ident::from(name).with_table(alias.to_string()).to_string()
+		}));
+	}
+
+	let left_keys = join.joins.iter().filter(|j| j.kind == JoinKind::Left);
+	columns.extend(left_keys.map(|j| j.key.to_string()));
+
+	let sql = format!("SELECT {} FROM {}{}", columns.join(", "), join, filter);
+
+	Ok((sql, lens))
+}
+
+/// Splits a row of [`join_sql`] into the tables and checks which tables were
+/// matched.
+fn join_parts(
+	join: &Join,
+	row: Row,
+	lens: &[usize],
+) -> Result<Vec<(Row, bool)>, Error> {
+	let columns = lens.iter().sum::<usize>();
+	let left = join.joins.iter().filter(|j| j.kind == JoinKind::Left);
+	let expected = columns + left.count();
+	if row.len() != expected {
+		return Err(Error::Deserialize(
+			format!(
+				"expected {} columns but the row has {}",
+				expected,
+				row.len()
+			)
+			.into(),
+		));
+	}
+
+	let mut key = columns;
+	let mut matched = vec![true];
+	for table in &join.joins {
+		matched.push(match table.kind {
+			JoinKind::Inner => true,
+			JoinKind::Left => {
+				key += 1;
+				!row.is_null(key - 1)?
+			}
+		});
+	}
+
+	Ok(row.split(lens)?.into_iter().zip(matched).collect())
+}
+
+/// Builds the statement of [`Connection::aggregate`].
+pub(crate) fn aggregate_sql(
+	table: &Ident,
+	aggregates: &Aggregates,
+	filter: &Filter,
+) -> String {
+	let mut formatter = filter.to_formatter();
+	formatter.aggregates = Some(aggregates);
+
+	let mut sql = String::from("SELECT ");
+	aggregates.fmt_columns(&mut sql).unwrap();
+	write!(&mut sql, " FROM {}{}", table, formatter).unwrap();
+
+	sql
+}
+
+/// Builds the statement of [`Connection::update`], the params of the where
+/// clause come after the params of the item.
+pub(crate) fn update_sql<U>(table: &Ident, item: &U, whr: &Where) -> String
+where
+	U: ToRow + ?Sized,
+{
+	let mut formatter = whr.to_formatter();
+	formatter.param_start = item.params_len();
+
+	let mut sql = format!("UPDATE {} SET ", table);
+	item.update_columns(&mut sql);
+	write!(&mut sql, "{}", formatter).unwrap();
+
+	sql
+}
+
+/// Builds the statement of [`Connection::delete`].
+pub(crate) fn delete_sql(table: &Ident, whr: &Where) -> String {
+	format!("DELETE FROM {}{}", table, whr)
+}
+
+/// The column [`Connection::select_page`] selects the total into.
+const TOTAL_COLUMN: Ident = Ident::new("__total");
+
 #[derive(Debug, thiserror::Error)]
 #[non_exhaustive]
 pub enum Error {
@@ -41,6 +211,9 @@ pub enum Error {
 	#[error("Other Postgres error {0}")]
 	Other(PgError),
 
+	#[error("Invalid cursor {0}")]
+	Cursor(#[from] CursorError),
+
 	#[error("Deserialization error {0}")]
 	Deserialize(Box<dyn std::error::Error + Send + Sync>),
 
@@ -65,49 +238,230 @@ impl From<PgError> for Error {
 }
 
 #[derive(Debug)]
-pub struct ConnectionOwned(pub(crate) Object);
+pub struct ConnectionOwned {
+	inner: OwnedInner,
+}
+
+#[derive(Debug)]
+enum OwnedInner {
+	Pooled(Object),
+	Client(PlainClient),
+}
+
+/// A client which does not belong to a pool.
+#[derive(Debug)]
+struct PlainClient {
+	client: Client,
+	/// The task driving the connection, if it was spawned by us
+	conn_task: Option<JoinHandle<()>>,
+	statement_cache: StatementCache,
+	metrics: Metrics,
+}
+
+impl PlainClient {
+	fn new(client: Client, conn_task: Option<JoinHandle<()>>) -> Self {
+		Self {
+			client,
+			conn_task,
+			statement_cache: StatementCache::default(),
+			metrics: Metrics {
+				created: Instant::now(),
+				recycled: None,
+				recycle_count: 0,
+			},
+		}
+	}
+}
+
+impl Drop for PlainClient {
+	fn drop(&mut self) {
+		if let Some(task) = &self.conn_task {
+			task.abort();
+		}
+	}
+}
+
+/// The prepared statements of a [`PlainClient`].
+#[derive(Debug, Default)]
+struct StatementCache {
+	inner: RwLock<HashMap<(String, Vec<Type>), Statement>>,
+}
+
+impl StatementCache {
+	async fn prepare<C>(
+		&self,
+		client: &C,
+		query: &str,
+		types: &[Type],
+	) -> Result<Statement, PgError>
+	where
+		C: GenericClient,
+	{
+		let key = (query.to_string(), types.to_vec());
+		if let Some(stmt) = self.inner.read().unwrap().get(&key) {
+			return Ok(stmt.clone());
+		}
+
+		let stmt = client.prepare_typed(query, types).await?;
+		self.inner.write().unwrap().insert(key, stmt.clone());
+
+		Ok(stmt)
+	}
+}
 
 impl ConnectionOwned {
-	pub fn connection(&self) -> Connection {
-		Connection {
-			inner: ConnectionInner::Client(&self.0),
+	pub(crate) fn from_pooled(object: Object) -> Self {
+		Self {
+			inner: OwnedInner::Pooled(object),
+		}
+	}
+
+	/// Creates a connection from a [`tokio_postgres::Client`] which does not
+	/// belong to a pool.
+	///
+	/// The connection task of the client needs to be driven by the caller.
+	/// Statements prepared with [`Connection::prepare_cached`] are cached for
+	/// the lifetime of the returned value.
+	pub fn from_client(client: Client) -> Self {
+		Self {
+			inner: OwnedInner::Client(PlainClient::new(client, None)),
 		}
 	}
 
-	pub async fn transaction<'a>(
-		&'a mut self,
-	) -> Result<Transaction<'a>, Error> {
-		Ok(Transaction {
-			inner: self.0.transaction().await.map_err(Error::from)?,
+	/// Opens a single connection without a pool.
+	///
+	/// The config is a connection string as described in
+	/// [`tokio_postgres::Config`], the connection gets closed when the
+	/// returned value is dropped.
+	///
+	/// ## Example
+	/// ```no_run
+	/// # use fire_postgres::connection::ConnectionOwned;
+	/// # async fn run() -> Result<(), fire_postgres::Error> {
+	/// let conn = ConnectionOwned::connect(
+	/// 	"host=localhost user=postgres dbname=test"
+	/// ).await?;
+	///
+	/// conn.connection().batch_execute("SELECT 1").await?;
+	/// # Ok(())
+	/// # }
+	/// ```
+	pub async fn connect(config: &str) -> Result<Self, Error> {
+		let (client, connection) = tokio_postgres::connect(config, NoTls)
+			.await
+			.map_err(Error::from)?;
+
+		let conn_task = tokio::spawn(async move {
+			if let Err(e) = connection.await {
+				error!("connection error {e}");
+			}
+		});
+
+		Ok(Self {
+			inner: OwnedInner::Client(PlainClient::new(
+				client,
+				Some(conn_task),
+			)),
 		})
 	}
 
+	pub fn connection(&self) -> Connection<'_> {
+		Connection {
+			inner: match &self.inner {
+				OwnedInner::Pooled(object) => ConnectionInner::Client(object),
+				OwnedInner::Client(client) => ConnectionInner::Plain(client),
+			},
+		}
+	}
+
+	pub async fn transaction(&mut self) -> Result<Transaction<'_>, Error> {
+		let inner = match &mut self.inner {
+			OwnedInner::Pooled(object) => TransactionInner::Pooled(
+				object.transaction().await.map_err(Error::from)?,
+			),
+			OwnedInner::Client(client) => {
+				TransactionInner::Plain(PlainTransaction {
+					tr: client
+						.client
+						.transaction()
+						.await
+						.map_err(Error::from)?,
+					statement_cache: &client.statement_cache,
+				})
+			}
+		};
+
+		Ok(Transaction { inner })
+	}
+
+	/// Returns the pool metrics of this connection
+	///
+	/// A connection which does not belong to a pool only knows when it was
+	/// created.
 	pub fn metrics(&self) -> &Metrics {
-		Object::metrics(&self.0)
+		match &self.inner {
+			OwnedInner::Pooled(object) => Object::metrics(object),
+			OwnedInner::Client(client) => &client.metrics,
+		}
 	}
 }
 
 #[derive(Debug)]
 pub struct Transaction<'a> {
-	inner: deadpool_postgres::Transaction<'a>,
+	inner: TransactionInner<'a>,
+}
+
+#[derive(Debug)]
+enum TransactionInner<'a> {
+	Pooled(deadpool_postgres::Transaction<'a>),
+	Plain(PlainTransaction<'a>),
+}
+
+/// A transaction of a [`PlainClient`].
+struct PlainTransaction<'a> {
+	tr: tokio_postgres::Transaction<'a>,
+	statement_cache: &'a StatementCache,
+}
+
+impl fmt::Debug for PlainTransaction<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("PlainTransaction")
+			.field("statement_cache", &self.statement_cache)
+			.finish_non_exhaustive()
+	}
 }
 
 impl<'a> Transaction<'a> {
 	/// Returns a connection to the database
 	pub fn connection(&self) -> Connection<'_> {
 		Connection {
-			inner: ConnectionInner::Transaction(&self.inner),
+			inner: match &self.inner {
+				TransactionInner::Pooled(tr) => {
+					ConnectionInner::Transaction(tr)
+				}
+				TransactionInner::Plain(tr) => {
+					ConnectionInner::PlainTransaction(tr)
+				}
+			},
 		}
 	}
 
 	/// See [`tokio_postgres::Transaction::commit()`]
 	pub async fn commit(self) -> Result<(), Error> {
-		self.inner.commit().await.map_err(Error::from)
+		match self.inner {
+			TransactionInner::Pooled(tr) => tr.commit().await,
+			TransactionInner::Plain(tr) => tr.tr.commit().await,
+		}
+		.map_err(Error::from)
 	}
 
 	/// See [`tokio_postgres::Transaction::rollback()`]
 	pub async fn rollback(self) -> Result<(), Error> {
-		self.inner.rollback().await.map_err(Error::from)
+		match self.inner {
+			TransactionInner::Pooled(tr) => tr.rollback().await,
+			TransactionInner::Plain(tr) => tr.tr.rollback().await,
+		}
+		.map_err(Error::from)
 	}
 }
 
@@ -120,32 +474,64 @@ pub struct Connection<'a> {
 enum ConnectionInner<'a> {
 	Client(&'a ClientWrapper),
 	Transaction(&'a deadpool_postgres::Transaction<'a>),
+	Plain(&'a PlainClient),
+	PlainTransaction(&'a PlainTransaction<'a>),
+	#[cfg(feature = "testing")]
+	Mock(&'a MockConnection),
+}
+
+#[cfg(feature = "testing")]
+impl<'a> Connection<'a> {
+	pub(crate) fn from_mock(mock: &'a MockConnection) -> Self {
+		Self {
+			inner: ConnectionInner::Mock(mock),
+		}
+	}
 }
 
 impl Connection<'_> {
 	// select
-
-	// how about the columns are a separat parameter, which contains
-	// an exact size iterator, and implementors can call
-	// select("table", R::select_columns(), filter)
-	// or select("table", &["column1", "column2"], filter)
 	pub async fn select<R>(
 		&self,
-		table: &str,
+		table: impl Into<Ident>,
 		filter: impl Borrow<Filter<'_>>,
 	) -> Result<Vec<R>, Error>
 	where
 		R: FromRowOwned + NamedColumns,
 	{
-		let sql = format!(
-			"SELECT {} FROM \"{}\"{}",
-			R::select_columns(),
-			table,
-			filter.borrow()
-		);
-		let stmt = self.prepare_cached(&sql).await?;
+		let sql = select_sql::<R>(&table.into(), filter.borrow());
+
+		self.query_sql(&sql, filter.borrow().params.iter_to_sql())
+			.await?
+			.map(|row| {
+				row.and_then(|row| {
+					R::from_row_owned(row).map_err(Error::Deserialize)
+				})
+			})
+			.try_collect()
+			.await
+	}
 
-		self.query_raw(&stmt, filter.borrow().params.iter_to_sql())
+	/// Selects only the given columns, in that order.
+	///
+	/// This allows to use tuples or `[T; 1]` which don't know their column
+	/// names, for example `select_columns::<(i32, String)>("users", &["id",
+	/// "name"], filter)`.
+	///
+	/// Returns an error if no columns are given.
+	pub async fn select_columns<R, C>(
+		&self,
+		table: impl Into<Ident>,
+		columns: &[C],
+		filter: impl Borrow<Filter<'_>>,
+	) -> Result<Vec<R>, Error>
+	where
+		R: FromRowOwned,
+		C: AsRef<str>,
+	{
+		let sql = select_columns_sql(&table.into(), columns, filter.borrow())?;
+
+		self.query_sql(&sql, filter.borrow().params.iter_to_sql())
 			.await?
 			.map(|row| {
 				row.and_then(|row| {
@@ -156,10 +542,36 @@ impl Connection<'_> {
 			.await
 	}
 
+	/// Selects from several joined tables, the columns of the filter need to
+	/// be qualified with the alias of their table.
+	///
+	/// The row needs one part per table, see [`FromJoinedRow`].
+	pub async fn select_join<R>(
+		&self,
+		join: &Join,
+		filter: impl Borrow<Filter<'_>>,
+	) -> Result<Vec<R>, Error>
+	where
+		R: FromJoinedRow,
+	{
+		let (sql, lens) = join_sql::<R>(join, filter.borrow())?;
+
+		self.query_sql(&sql, filter.borrow().params.iter_to_sql())
+			.await?
+			.map(|row| {
+				row.and_then(|row| {
+					let parts = join_parts(join, row, &lens)?;
+					R::from_parts(parts).map_err(Error::Deserialize)
+				})
+			})
+			.try_collect()
+			.await
+	}
+
 	// select_one
 	pub async fn select_one<R>(
 		&self,
-		table: &str,
+		table: impl Into<Ident>,
 		filter: impl Borrow<Filter<'_>>,
 	) -> Result<R, Error>
 	where
@@ -171,16 +583,10 @@ impl Connection<'_> {
 			formatter.limit = &Limit::Fixed(1);
 		}
 
-		let sql = format!(
-			"SELECT {} FROM \"{}\"{}",
-			R::select_columns(),
-			table,
-			formatter
-		);
-		let stmt = self.prepare_cached(&sql).await?;
+		let sql = select_sql::<R>(&table.into(), formatter);
 
 		let row = self
-			.query_raw_opt(&stmt, filter.borrow().params.iter_to_sql())
+			.query_sql_opt(&sql, filter.borrow().params.iter_to_sql())
 			.await
 			.and_then(|opt| opt.ok_or(Error::ExpectedOneRow))?;
 
@@ -190,7 +596,7 @@ impl Connection<'_> {
 	// select_opt
 	pub async fn select_opt<R>(
 		&self,
-		table: &str,
+		table: impl Into<Ident>,
 		filter: impl Borrow<Filter<'_>>,
 	) -> Result<Option<R>, Error>
 	where
@@ -202,64 +608,220 @@ impl Connection<'_> {
 			formatter.limit = &Limit::Fixed(1);
 		}
 
-		let sql = format!(
-			"SELECT {} FROM \"{}\"{}",
-			R::select_columns(),
-			table,
-			formatter
-		);
-		let stmt = self.prepare_cached(&sql).await?;
+		let sql = select_sql::<R>(&table.into(), formatter);
 
-		self.query_raw_opt(&stmt, filter.borrow().params.iter_to_sql())
+		self.query_sql_opt(&sql, filter.borrow().params.iter_to_sql())
 			.await
 	}
 
+	/// Selects a page of rows with keyset pagination.
+	///
+	/// The filter needs an order by which only contains ASC or DESC columns
+	/// and they need to be unique together, for example `"created" DESC, "id"
+	/// DESC`. The limit of the filter is the page size. Pass the cursor of
+	/// the previous page to get the next one.
+	///
+	/// All order by columns need to be selected by `R`.
+	pub async fn select_keyset<'a, R>(
+		&self,
+		table: impl Into<Ident>,
+		mut filter: Filter<'a>,
+		after: Option<&'a Cursor>,
+	) -> Result<Page<R>, Error>
+	where
+		R: FromRowOwned + NamedColumns,
+	{
+		if let Some(cursor) = after {
+			filter = filter.after(cursor)?;
+		}
+		let limit = keyset_limit(&mut filter)?;
+
+		let sql = select_sql::<R>(&table.into(), &filter);
+
+		let rows: Vec<Row> = self
+			.query_sql(&sql, filter.params.iter_to_sql())
+			.await?
+			.try_collect()
+			.await?;
+
+		Page::from_rows(rows, &filter.order_by, limit)
+	}
+
 	/// count
 	///
 	/// A column is required because you should select a column which has some
 	/// indexes on it, this makes the call a lot cheaper
 	pub async fn count(
 		&self,
-		table: &str,
-		column: &str,
+		table: impl Into<Ident>,
+		column: impl Into<Ident>,
 		filter: impl Borrow<Filter<'_>>,
-	) -> Result<u32, Error> {
+	) -> Result<u64, Error> {
 		let sql = format!(
-			"SELECT COUNT(\"{}\") FROM \"{}\"{}",
-			table,
-			column,
+			"SELECT COUNT({}) FROM {}{}",
+			column.into(),
+			table.into(),
 			filter.borrow()
 		);
-		let stmt = self.prepare_cached(&sql).await?;
 
 		let row: Row = self
-			.query_raw_opt(&stmt, filter.borrow().params.iter_to_sql())
+			.query_sql_opt(&sql, filter.borrow().params.iter_to_sql())
 			.await
 			.and_then(|opt| opt.ok_or(Error::ExpectedOneRow))?;
 
-		Ok(row.get(0))
+		// COUNT returns a bigint
+		let count: i64 = row.try_get(0)?;
+		u64::try_from(count).map_err(|e| Error::Deserialize(e.into()))
+	}
+
+	/// Selects the group by columns and the aggregates, one row per group.
+	///
+	/// The having params come after the where params of the filter, see
+	/// [`Aggregates`].
+	pub async fn aggregate<R>(
+		&self,
+		table: impl Into<Ident>,
+		aggregates: &Aggregates<'_>,
+		filter: impl Borrow<Filter<'_>>,
+	) -> Result<Vec<R>, Error>
+	where
+		R: FromRowOwned,
+	{
+		let filter = filter.borrow();
+		let sql = aggregate_sql(&table.into(), aggregates, filter);
+		let params: Vec<_> =
+			aggregates.params(filter).map(|p| p.data.as_ref()).collect();
+
+		self.query_sql(&sql, params)
+			.await?
+			.map(|row| {
+				row.and_then(|row| {
+					R::from_row_owned(row).map_err(Error::Deserialize)
+				})
+			})
+			.try_collect()
+			.await
+	}
+
+	/// Selects the aggregates of all rows which match the filter, without
+	/// group by columns there is always exactly one row.
+	pub async fn aggregate_one<R>(
+		&self,
+		table: impl Into<Ident>,
+		aggregates: &Aggregates<'_>,
+		filter: impl Borrow<Filter<'_>>,
+	) -> Result<R, Error>
+	where
+		R: FromRowOwned,
+	{
+		let filter = filter.borrow();
+		let sql = aggregate_sql(&table.into(), aggregates, filter);
+		let params: Vec<_> =
+			aggregates.params(filter).map(|p| p.data.as_ref()).collect();
+
+		self.query_sql_opt(&sql, params)
+			.await
+			.and_then(|opt| opt.ok_or(Error::ExpectedOneRow))
+	}
+
+	/// Selects a page of rows together with the number of all rows which
+	/// match the filter.
+	///
+	/// The page starts at zero, the limit and offset of the filter are
+	/// replaced. The total is counted in the same query with
+	/// `COUNT(*) OVER ()`, only a page after the last row needs a second
+	/// query.
+	pub async fn select_page<R>(
+		&self,
+		table: impl Into<Ident>,
+		filter: impl Borrow<Filter<'_>>,
+		page: usize,
+		per_page: usize,
+	) -> Result<OffsetPage<R>, Error>
+	where
+		R: FromRowOwned + NamedColumns,
+	{
+		let table = table.into();
+		let filter = filter.borrow();
+
+		let mut formatter = filter.to_formatter();
+		let limit = Limit::Fixed(per_page);
+		let offset = Offset::Fixed(page.saturating_mul(per_page));
+		formatter.limit = &limit;
+		formatter.offset = &offset;
+
+		let sql = format!(
+			"SELECT {}, COUNT(*) OVER () AS {} FROM {}{}",
+			R::select_columns(),
+			TOTAL_COLUMN,
+			table,
+			formatter
+		);
+
+		// the limit and offset params of the filter are not used
+		let param_count =
+			filter.whr.param_count() + filter.order_by.param_count();
+		let rows: Vec<Row> = self
+			.query_sql(&sql, filter.params.iter_to_sql().take(param_count))
+			.await?
+			.try_collect()
+			.await?;
+
+		let total = match rows.first() {
+			Some(row) => row.try_get::<_, i64>(TOTAL_COLUMN.as_str())?,
+			None if page == 0 => 0,
+			None => {
+				let sql =
+					format!("SELECT COUNT(*) FROM {}{}", table, filter.whr);
+				let param_count = filter.whr.param_count();
+
+				let row: Row = self
+					.query_sql_opt(
+						&sql,
+						filter.params.iter_to_sql().take(param_count),
+					)
+					.await
+					.and_then(|opt| opt.ok_or(Error::ExpectedOneRow))?;
+
+				row.try_get(0)?
+			}
+		};
+
+		let items = rows
+			.into_iter()
+			.map(|row| R::from_row_owned(row).map_err(Error::Deserialize))
+			.collect::<Result<_, _>>()?;
+
+		Ok(OffsetPage {
+			items,
+			total: total as u64,
+			page,
+			per_page,
+		})
 	}
 
 	// insert one
-	pub async fn insert<U>(&self, table: &str, item: &U) -> Result<(), Error>
+	pub async fn insert<U>(
+		&self,
+		table: impl Into<Ident>,
+		item: &U,
+	) -> Result<(), Error>
 	where
 		U: ToRow,
 	{
-		let mut sql = format!("INSERT INTO \"{table}\" (");
+		let mut sql = format!("INSERT INTO {} (", table.into());
 		item.insert_columns(&mut sql);
 		sql.push_str(") VALUES (");
 		item.insert_values(&mut sql);
 		sql.push(')');
 
-		let stmt = self.prepare_cached(&sql).await?;
-
-		self.execute_raw(&stmt, item.params()).await.map(|_| ())
+		self.execute_sql(&sql, item.params()).await.map(|_| ())
 	}
 
 	// insert_many
 	pub async fn insert_many<U, I>(
 		&self,
-		table: &str,
+		table: impl Into<Ident>,
 		items: I,
 	) -> Result<(), Error>
 	where
@@ -268,15 +830,14 @@ impl Connection<'_> {
 		I::Item: Borrow<U>,
 	{
 		let sql = format!(
-			"INSERT INTO \"{}\" ({}) VALUES ({})",
-			table,
+			"INSERT INTO {} ({}) VALUES ({})",
+			table.into(),
 			U::insert_columns(),
 			U::insert_values()
 		);
-		let stmt = self.prepare_cached(&sql).await?;
 
 		for item in items {
-			self.execute_raw(&stmt, item.borrow().params()).await?;
+			self.execute_sql(&sql, item.borrow().params()).await?;
 		}
 
 		Ok(())
@@ -285,7 +846,7 @@ impl Connection<'_> {
 	// update
 	pub async fn update<U>(
 		&self,
-		table: &str,
+		table: impl Into<Ident>,
 		item: &U,
 		filter: impl Borrow<WhereFilter<'_>>,
 	) -> Result<(), Error>
@@ -293,19 +854,12 @@ impl Connection<'_> {
 		U: ToRow,
 	{
 		let filter = filter.borrow();
-		let mut formatter = filter.whr.to_formatter();
-		formatter.param_start = item.params_len();
-
-		let mut sql = format!("UPDATE \"{table}\" SET ");
-		item.update_columns(&mut sql);
-		write!(&mut sql, "{}", formatter).unwrap();
-
-		let stmt = self.prepare_cached(&sql).await?;
+		let sql = update_sql(&table.into(), item, &filter.whr);
 
 		// we need to merge both params
 
-		self.execute_raw(
-			&stmt,
+		self.execute_sql(
+			&sql,
 			TwoExactSize(item.params(), filter.params.iter_to_sql()),
 		)
 		.await
@@ -315,17 +869,72 @@ impl Connection<'_> {
 	// delete
 	pub async fn delete(
 		&self,
-		table: &str,
+		table: impl Into<Ident>,
 		filter: impl Borrow<WhereFilter<'_>>,
 	) -> Result<(), Error> {
-		let sql = format!("DELETE FROM \"{}\"{}", table, filter.borrow());
-		let stmt = self.prepare_cached(&sql).await?;
+		let sql = delete_sql(&table.into(), &filter.borrow().whr);
 
-		self.execute_raw(&stmt, filter.borrow().params.iter_to_sql())
+		self.execute_sql(&sql, filter.borrow().params.iter_to_sql())
 			.await
 			.map(|_| ())
 	}
 
+	/// Prepares the query with a cached statement and runs it.
+	async fn query_sql<P, I>(
+		&self,
+		sql: &str,
+		params: I,
+	) -> Result<RowStream, Error>
+	where
+		P: BorrowToSql,
+		I: IntoIterator<Item = P>,
+		I::IntoIter: ExactSizeIterator,
+	{
+		#[cfg(feature = "testing")]
+		if let ConnectionInner::Mock(mock) = &self.inner {
+			return mock.query(sql, params).map(RowStream::from);
+		}
+
+		let stmt = self.prepare_cached(sql).await?;
+		self.query_raw(&stmt, params).await
+	}
+
+	async fn query_sql_opt<R, P, I>(
+		&self,
+		sql: &str,
+		params: I,
+	) -> Result<Option<R>, Error>
+	where
+		R: FromRowOwned,
+		P: BorrowToSql,
+		I: IntoIterator<Item = P>,
+		I::IntoIter: ExactSizeIterator,
+	{
+		let stream = self.query_sql(sql, params).await?;
+
+		stream_opt(stream).await
+	}
+
+	/// Prepares the statement with a cached statement and executes it.
+	async fn execute_sql<P, I>(
+		&self,
+		sql: &str,
+		params: I,
+	) -> Result<u64, Error>
+	where
+		P: BorrowToSql,
+		I: IntoIterator<Item = P>,
+		I::IntoIter: ExactSizeIterator,
+	{
+		#[cfg(feature = "testing")]
+		if let ConnectionInner::Mock(mock) = &self.inner {
+			return mock.execute(sql, params);
+		}
+
+		let stmt = self.prepare_cached(sql).await?;
+		self.execute_raw(&stmt, params).await
+	}
+
 	/// Like [`tokio_postgres::Client::prepare_typed()`] but uses a cached
 	/// statement if one exists.
 	pub async fn prepare_cached(
@@ -339,6 +948,18 @@ impl Connection<'_> {
 			ConnectionInner::Transaction(tr) => {
 				tr.prepare_cached(query).await.map_err(Error::from)
 			}
+			ConnectionInner::Plain(client) => client
+				.statement_cache
+				.prepare(&client.client, query, &[])
+				.await
+				.map_err(Error::from),
+			ConnectionInner::PlainTransaction(tr) => tr
+				.statement_cache
+				.prepare(&tr.tr, query, &[])
+				.await
+				.map_err(Error::from),
+			#[cfg(feature = "testing")]
+			ConnectionInner::Mock(_) => Err(unsupported_by_mock()),
 		}
 	}
 
@@ -351,6 +972,14 @@ impl Connection<'_> {
 			ConnectionInner::Transaction(tr) => {
 				tr.prepare(query).await.map_err(Error::from)
 			}
+			ConnectionInner::Plain(client) => {
+				client.client.prepare(query).await.map_err(Error::from)
+			}
+			ConnectionInner::PlainTransaction(tr) => {
+				tr.tr.prepare(query).await.map_err(Error::from)
+			}
+			#[cfg(feature = "testing")]
+			ConnectionInner::Mock(_) => Err(unsupported_by_mock()),
 		}
 	}
 
@@ -370,6 +999,18 @@ impl Connection<'_> {
 				.prepare_typed_cached(query, types)
 				.await
 				.map_err(Error::from),
+			ConnectionInner::Plain(client) => client
+				.statement_cache
+				.prepare(&client.client, query, types)
+				.await
+				.map_err(Error::from),
+			ConnectionInner::PlainTransaction(tr) => tr
+				.statement_cache
+				.prepare(&tr.tr, query, types)
+				.await
+				.map_err(Error::from),
+			#[cfg(feature = "testing")]
+			ConnectionInner::Mock(_) => Err(unsupported_by_mock()),
 		}
 	}
 
@@ -388,6 +1029,18 @@ impl Connection<'_> {
 				.prepare_typed(query, parameter_types)
 				.await
 				.map_err(Error::from),
+			ConnectionInner::Plain(client) => client
+				.client
+				.prepare_typed(query, parameter_types)
+				.await
+				.map_err(Error::from),
+			ConnectionInner::PlainTransaction(tr) => tr
+				.tr
+				.prepare_typed(query, parameter_types)
+				.await
+				.map_err(Error::from),
+			#[cfg(feature = "testing")]
+			ConnectionInner::Mock(_) => Err(unsupported_by_mock()),
 		}
 	}
 
@@ -429,6 +1082,14 @@ impl Connection<'_> {
 			ConnectionInner::Transaction(tr) => {
 				tr.query_one(statement, params).await?
 			}
+			ConnectionInner::Plain(client) => {
+				client.client.query_one(statement, params).await?
+			}
+			ConnectionInner::PlainTransaction(tr) => {
+				tr.tr.query_one(statement, params).await?
+			}
+			#[cfg(feature = "testing")]
+			ConnectionInner::Mock(_) => return Err(unsupported_by_mock()),
 		};
 
 		R::from_row_owned(row.into()).map_err(Error::Deserialize)
@@ -451,6 +1112,14 @@ impl Connection<'_> {
 			ConnectionInner::Transaction(tr) => {
 				tr.query_opt(statement, params).await?
 			}
+			ConnectionInner::Plain(client) => {
+				client.client.query_opt(statement, params).await?
+			}
+			ConnectionInner::PlainTransaction(tr) => {
+				tr.tr.query_opt(statement, params).await?
+			}
+			#[cfg(feature = "testing")]
+			ConnectionInner::Mock(_) => return Err(unsupported_by_mock()),
 		};
 
 		R::from_row_owned(try2!(row).into())
@@ -472,18 +1141,8 @@ impl Connection<'_> {
 		I::IntoIter: ExactSizeIterator,
 	{
 		let stream = self.query_raw(statement, params).await?;
-		pin_mut!(stream);
-
-		let row = match stream.try_next().await? {
-			Some(row) => row,
-			None => return Ok(None),
-		};
-
-		if stream.try_next().await?.is_some() {
-			return Err(Error::ExpectedOneRow);
-		}
 
-		R::from_row_owned(row).map(Some).map_err(Error::Deserialize)
+		stream_opt(stream).await
 	}
 
 	/// See [`tokio_postgres::Client::query_raw()`]
@@ -505,6 +1164,14 @@ impl Connection<'_> {
 			ConnectionInner::Transaction(tr) => {
 				tr.query_raw(statement, params).await?
 			}
+			ConnectionInner::Plain(client) => {
+				client.client.query_raw(statement, params).await?
+			}
+			ConnectionInner::PlainTransaction(tr) => {
+				tr.tr.query_raw(statement, params).await?
+			}
+			#[cfg(feature = "testing")]
+			ConnectionInner::Mock(_) => return Err(unsupported_by_mock()),
 		};
 
 		Ok(row_stream.into())
@@ -526,6 +1193,16 @@ impl Connection<'_> {
 			ConnectionInner::Transaction(tr) => {
 				tr.execute(statement, params).await.map_err(Error::from)
 			}
+			ConnectionInner::Plain(client) => client
+				.client
+				.execute(statement, params)
+				.await
+				.map_err(Error::from),
+			ConnectionInner::PlainTransaction(tr) => {
+				tr.tr.execute(statement, params).await.map_err(Error::from)
+			}
+			#[cfg(feature = "testing")]
+			ConnectionInner::Mock(_) => Err(unsupported_by_mock()),
 		}
 	}
 
@@ -549,6 +1226,18 @@ impl Connection<'_> {
 			ConnectionInner::Transaction(tr) => {
 				tr.execute_raw(statement, params).await.map_err(Error::from)
 			}
+			ConnectionInner::Plain(client) => client
+				.client
+				.execute_raw(statement, params)
+				.await
+				.map_err(Error::from),
+			ConnectionInner::PlainTransaction(tr) => tr
+				.tr
+				.execute_raw(statement, params)
+				.await
+				.map_err(Error::from),
+			#[cfg(feature = "testing")]
+			ConnectionInner::Mock(_) => Err(unsupported_by_mock()),
 		}
 	}
 
@@ -561,10 +1250,45 @@ impl Connection<'_> {
 			ConnectionInner::Transaction(tr) => {
 				tr.batch_execute(query).await.map_err(Error::from)
 			}
+			ConnectionInner::Plain(client) => client
+				.client
+				.batch_execute(query)
+				.await
+				.map_err(Error::from),
+			ConnectionInner::PlainTransaction(tr) => {
+				tr.tr.batch_execute(query).await.map_err(Error::from)
+			}
+			#[cfg(feature = "testing")]
+			ConnectionInner::Mock(mock) => {
+				mock.execute(query, slice_iter(&[])).map(|_| ())
+			}
 		}
 	}
 }
 
+async fn stream_opt<R>(stream: RowStream) -> Result<Option<R>, Error>
+where
+	R: FromRowOwned,
+{
+	pin_mut!(stream);
+
+	let row = match stream.try_next().await? {
+		Some(row) => row,
+		None => return Ok(None),
+	};
+
+	if stream.try_next().await?.is_some() {
+		return Err(Error::ExpectedOneRow);
+	}
+
+	R::from_row_owned(row).map(Some).map_err(Error::Deserialize)
+}
+
+#[cfg(feature = "testing")]
+fn unsupported_by_mock() -> Error {
+	Error::Unknown("not supported by the mock connection".into())
+}
+
 fn slice_iter<'a>(
 	s: &'a [&'a (dyn ToSql + Sync)],
 ) -> impl ExactSizeIterator<Item = &'a dyn ToSql> + 'a {
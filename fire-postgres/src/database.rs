@@ -100,7 +100,13 @@ impl Database {
 					todo!("what is this error {e:?}?")
 				}
 			})
-			.map(ConnectionOwned)
+			.map(ConnectionOwned::from_pooled)
+	}
+
+	/// Closes the pool, connections which are in use get closed when they
+	/// are returned.
+	pub fn close(&self) {
+		self.pool.close();
 	}
 
 	pub fn migrations(&self) -> Migrations {
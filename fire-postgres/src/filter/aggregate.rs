@@ -0,0 +1,393 @@
+use std::fmt;
+
+use postgres_types::Type;
+use tokio_postgres::types::ToSql;
+
+use super::{
+	fmt_where, Comparison, Filter, Param, ParamData, Params, Where, WherePart,
+};
+use crate::Ident;
+
+/// An aggregate function like `COUNT(*)` or `SUM("amount")`.
+///
+/// Without an alias postgres names the result column after the function,
+/// for example `count` or `sum`.
+#[derive(Debug, Clone)]
+pub struct Aggregate {
+	pub kind: AggregateKind,
+	/// If no column is set all rows are counted `COUNT(*)`
+	pub column: Option<Ident>,
+	pub cast: Option<Type>,
+	pub alias: Option<Ident>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggregateKind {
+	Count,
+	CountDistinct,
+	Sum,
+	Avg,
+	Min,
+	Max,
+}
+
+impl Aggregate {
+	fn new(kind: AggregateKind, column: Option<Ident>) -> Self {
+		Self {
+			kind,
+			column,
+			cast: None,
+			alias: None,
+		}
+	}
+
+	/// `COUNT(*)` counts all rows.
+	pub fn count_all() -> Self {
+		Self::new(AggregateKind::Count, None)
+	}
+
+	/// `COUNT("column")` counts the rows where the column is not null.
+	pub fn count(column: impl Into<Ident>) -> Self {
+		Self::new(AggregateKind::Count, Some(column.into()))
+	}
+
+	/// `COUNT(DISTINCT "column")`
+	pub fn count_distinct(column: impl Into<Ident>) -> Self {
+		Self::new(AggregateKind::CountDistinct, Some(column.into()))
+	}
+
+	/// `SUM("column")`, the sum of a `bigint` column is a `numeric`.
+	pub fn sum(column: impl Into<Ident>) -> Self {
+		Self::new(AggregateKind::Sum, Some(column.into()))
+	}
+
+	/// `AVG("column")`, the average of an integer column is a `numeric`.
+	pub fn avg(column: impl Into<Ident>) -> Self {
+		Self::new(AggregateKind::Avg, Some(column.into()))
+	}
+
+	/// `MIN("column")`
+	pub fn min(column: impl Into<Ident>) -> Self {
+		Self::new(AggregateKind::Min, Some(column.into()))
+	}
+
+	/// `MAX("column")`
+	pub fn max(column: impl Into<Ident>) -> Self {
+		Self::new(AggregateKind::Max, Some(column.into()))
+	}
+
+	/// Casts the result, for example to read the `numeric` average of an
+	/// integer column as a `float8`.
+	pub fn cast(mut self, ty: Type) -> Self {
+		self.cast = Some(ty);
+		self
+	}
+
+	/// Names the result column.
+	pub fn alias(mut self, alias: impl Into<Ident>) -> Self {
+		self.alias = Some(alias.into());
+		self
+	}
+
+	/// The name of the result column.
+	fn name(&self) -> Ident {
+		if let Some(alias) = &self.alias {
+			return alias.clone();
+		}
+
+		Ident::new(match self.kind {
+			AggregateKind::Count | AggregateKind::CountDistinct => "count",
+			AggregateKind::Sum => "sum",
+			AggregateKind::Avg => "avg",
+			AggregateKind::Min => "min",
+			AggregateKind::Max => "max",
+		})
+	}
+
+	/// Writes the function without the alias.
+	fn fmt_expr(&self, f: &mut impl fmt::Write) -> fmt::Result {
+		let func = match self.kind {
+			AggregateKind::Count => "COUNT(",
+			AggregateKind::CountDistinct => "COUNT(DISTINCT ",
+			AggregateKind::Sum => "SUM(",
+			AggregateKind::Avg => "AVG(",
+			AggregateKind::Min => "MIN(",
+			AggregateKind::Max => "MAX(",
+		};
+		f.write_str(func)?;
+
+		match &self.column {
+			Some(column) => write!(f, "{})", column)?,
+			None => f.write_str("*)")?,
+		}
+
+		match &self.cast {
+			Some(ty) => write!(f, "::{}", ty.name()),
+			None => Ok(()),
+		}
+	}
+}
+
+impl fmt::Display for Aggregate {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		self.fmt_expr(f)?;
+
+		match &self.alias {
+			Some(alias) => write!(f, " AS {}", alias),
+			None => Ok(()),
+		}
+	}
+}
+
+/// The aggregates of a query with the columns they are grouped by.
+///
+/// The group by columns are selected first followed by the aggregates, so
+/// the rows can be read as tuples or with a [`FromRow`](crate::FromRow)
+/// struct which uses the same names. The where clause, order by and limit
+/// come from the filter, the result columns can be used in the order by.
+///
+/// ## Example
+/// ```
+/// use fire_postgres::filter::{
+/// 	Aggregate, Aggregates, Comparison, DebugSql, Filter,
+/// };
+/// use tokio_postgres::types::Type;
+///
+/// let min = 10i64;
+/// let aggregates = Aggregates::new()
+/// 	.group_by("status")
+/// 	.select(Aggregate::count_all())
+/// 	.select(Aggregate::avg("amount").cast(Type::FLOAT8).alias("average"))
+/// 	.having(Aggregate::count_all(), Comparison::Gte, &min);
+///
+/// let active = true;
+/// let filter = Filter::new().and_eq("active", &active).order_desc("count");
+///
+/// assert_eq!(
+/// 	DebugSql::new().aggregate("orders", &aggregates, &filter),
+/// 	"SELECT \"status\", COUNT(*), AVG(\"amount\")::float8 AS \"average\" \
+/// 	FROM \"orders\" WHERE \"active\" = TRUE GROUP BY \"status\" \
+/// 	HAVING (COUNT(*) >= 10) ORDER BY \"count\" DESC"
+/// );
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct Aggregates<'a> {
+	pub group_by: Vec<Ident>,
+	pub aggregates: Vec<Aggregate>,
+	pub having: Where,
+	/// The params of the having clause
+	pub params: Params<'a>,
+}
+
+impl<'a> Aggregates<'a> {
+	pub fn new() -> Self {
+		Self {
+			group_by: vec![],
+			aggregates: vec![],
+			having: Where::new(),
+			params: Params::new(),
+		}
+	}
+
+	/// Groups by the column and selects it.
+	pub fn group_by(mut self, column: impl Into<Ident>) -> Self {
+		self.group_by.push(column.into());
+		self
+	}
+
+	/// Selects the aggregate after the group by columns.
+	pub fn select(mut self, aggregate: Aggregate) -> Self {
+		self.aggregates.push(aggregate);
+		self
+	}
+
+	/// Adds `AND aggregate comparison value` to the having clause.
+	pub fn having<T>(
+		self,
+		aggregate: Aggregate,
+		kind: Comparison,
+		value: &'a T,
+	) -> Self
+	where
+		T: ParamData + ToSql + Send + Sync,
+	{
+		self.condition(WherePart::And, aggregate, kind, value)
+	}
+
+	/// Adds `OR aggregate comparison value` to the having clause.
+	pub fn or_having<T>(
+		self,
+		aggregate: Aggregate,
+		kind: Comparison,
+		value: &'a T,
+	) -> Self
+	where
+		T: ParamData + ToSql + Send + Sync,
+	{
+		self.condition(WherePart::Or, aggregate, kind, value)
+	}
+
+	fn condition<T>(
+		mut self,
+		log: WherePart,
+		aggregate: Aggregate,
+		kind: Comparison,
+		value: &'a T,
+	) -> Self
+	where
+		T: ParamData + ToSql + Send + Sync,
+	{
+		let param = Param::new(aggregate.name(), value);
+
+		let mut sql = String::new();
+		aggregate.fmt_expr(&mut sql).unwrap();
+		let params = match kind {
+			Comparison::Eq if param.is_null() => {
+				sql.push_str(" IS NULL");
+				0
+			}
+			Comparison::Ne if param.is_null() => {
+				sql.push_str(" IS NOT NULL");
+				0
+			}
+			kind => {
+				sql.push(' ');
+				sql.push_str(kind.as_str());
+				sql.push_str(" $1");
+				1
+			}
+		};
+
+		if params > 0 {
+			self.params.push(param);
+		}
+
+		if !self.having.is_empty() {
+			self.having.push(log);
+		}
+		self.having.push(WherePart::Raw {
+			sql: sql.into(),
+			params,
+		});
+
+		self
+	}
+
+	/// Writes the group by columns and the aggregates separated by commas.
+	pub(crate) fn fmt_columns(&self, f: &mut impl fmt::Write) -> fmt::Result {
+		let columns = self.group_by.iter().map(|c| c as &dyn fmt::Display);
+		let aggregates = self.aggregates.iter().map(|a| a as &dyn fmt::Display);
+
+		for (i, column) in columns.chain(aggregates).enumerate() {
+			if i != 0 {
+				f.write_str(", ")?;
+			}
+			write!(f, "{}", column)?;
+		}
+
+		Ok(())
+	}
+
+	/// Writes the `GROUP BY` and `HAVING` clauses.
+	pub(super) fn fmt_group_by(
+		&self,
+		f: &mut fmt::Formatter<'_>,
+		param_num: &mut usize,
+	) -> fmt::Result {
+		for (i, column) in self.group_by.iter().enumerate() {
+			if i == 0 {
+				f.write_str(" GROUP BY ")?;
+			} else {
+				f.write_str(", ")?;
+			}
+			write!(f, "{}", column)?;
+		}
+
+		if !self.having.is_empty() {
+			f.write_str(" HAVING ")?;
+			fmt_where(&self.having, f, param_num)?;
+		}
+
+		Ok(())
+	}
+
+	/// Returns the params in the order they appear in the sql, the having
+	/// params come after the where params of the filter.
+	pub(crate) fn params<'b>(
+		&'b self,
+		filter: &'b Filter,
+	) -> impl Iterator<Item = &'b Param<'b>> {
+		let whr = filter.whr.param_count();
+
+		filter
+			.params
+			.iter()
+			.take(whr)
+			.chain(self.params.iter())
+			.chain(filter.params.iter().skip(whr))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use crate::filter;
+	use crate::filter::DebugSql;
+
+	#[test]
+	fn test_aggregate() {
+		assert_eq!(Aggregate::count_all().to_string(), "COUNT(*)");
+		assert_eq!(
+			Aggregate::count_distinct("user_id").to_string(),
+			r#"COUNT(DISTINCT "user_id")"#
+		);
+		assert_eq!(
+			Aggregate::avg("amount")
+				.cast(Type::FLOAT8)
+				.alias("average")
+				.to_string(),
+			r#"AVG("amount")::float8 AS "average""#
+		);
+
+		assert_eq!(Aggregate::count("id").name(), "count");
+		assert_eq!(Aggregate::max("id").alias("last").name(), "last");
+	}
+
+	#[test]
+	fn test_having() {
+		let min = 2i64;
+		let max = 100i64;
+		let none: Option<i32> = None;
+
+		let aggregates = Aggregates::new()
+			.group_by("status")
+			.select(Aggregate::count_all())
+			.having(Aggregate::count_all(), Comparison::Gte, &min)
+			.or_having(Aggregate::sum("amount"), Comparison::Lt, &max)
+			.having(Aggregate::max("amount"), Comparison::Ne, &none);
+		assert_eq!(aggregates.params.len(), 2);
+
+		let active = true;
+		let limit = 10i64;
+		let filter =
+			filter!("active" = &active ORDER "count" DESC LIMIT &limit);
+
+		// the having params are between the where and the limit params
+		let names: Vec<_> = aggregates
+			.params(&filter)
+			.map(|p| p.name.as_str())
+			.collect();
+		assert_eq!(names, ["active", "count", "sum", "limit"]);
+
+		assert_eq!(
+			DebugSql::new().aggregate("orders", &aggregates, &filter),
+			"SELECT \"status\", COUNT(*) FROM \"orders\" \
+			WHERE \"active\" = TRUE GROUP BY \"status\" \
+			HAVING (COUNT(*) >= 2) OR (SUM(\"amount\") < 100) \
+			AND (MAX(\"amount\") IS NOT NULL) \
+			ORDER BY \"count\" DESC LIMIT 10"
+		);
+	}
+}
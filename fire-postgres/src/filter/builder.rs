@@ -0,0 +1,1168 @@
+use std::mem;
+
+use tokio_postgres::types::ToSql;
+
+use crate::Ident;
+
+use super::{
+	placeholders, Comparison, Cursor, CursorError, Direction, Filter,
+	IntoColumn, IntoListColumn, JsonComparison, JsonPath, Keyset, Limit,
+	Offset, Operator, OrderByPart, Param, ParamData, Params, SqlStr, SubQuery,
+	TextSearch, Where, WhereFilter, WhereIdent, WhereOperation, WherePart,
+};
+
+/// Something where conditions can be added to at runtime.
+trait Conditions<'a> {
+	fn whr_mut(&mut self) -> &mut Where;
+
+	/// Adds a param which belongs to the where clause.
+	fn push_where_param(&mut self, param: Param<'a>);
+}
+
+impl<'a> Conditions<'a> for Filter<'a> {
+	fn whr_mut(&mut self) -> &mut Where {
+		&mut self.whr
+	}
+
+	fn push_where_param(&mut self, param: Param<'a>) {
+		// the order by, limit and offset params are always after the where
+		// params
+		let index = self.params.len()
+			- self.order_by.param_count()
+			- limit_offset_params(self);
+
+		self.params.insert(index, param);
+	}
+}
+
+fn limit_offset_params(f: &Filter) -> usize {
+	matches!(f.limit, Limit::Param) as usize
+		+ matches!(f.offset, Offset::Param) as usize
+}
+
+impl<'a> Conditions<'a> for WhereFilter<'a> {
+	fn whr_mut(&mut self) -> &mut Where {
+		&mut self.whr
+	}
+
+	fn push_where_param(&mut self, param: Param<'a>) {
+		self.params.push(param);
+	}
+}
+
+fn push_log<'a>(f: &mut impl Conditions<'a>, log: WherePart) {
+	let whr = f.whr_mut();
+	if !whr.is_empty() {
+		whr.push(log);
+	}
+}
+
+fn condition<'a>(
+	f: &mut impl Conditions<'a>,
+	log: WherePart,
+	kind: Operator,
+	path: Option<JsonPath>,
+	param: Param<'a>,
+) {
+	push_log(f, log);
+
+	let column = param.name.clone();
+	let kind = match kind {
+		Operator::Eq if param.is_null() => Operator::IsNull,
+		Operator::Ne if param.is_null() => Operator::IsNotNull,
+		kind => kind,
+	};
+
+	if !matches!(kind, Operator::IsNull | Operator::IsNotNull) {
+		f.push_where_param(param);
+	}
+
+	f.whr_mut().push(WhereOperation {
+		kind,
+		column,
+		path,
+		value: WhereIdent::Param,
+	});
+}
+
+/// Compares a column with another column or a sql expression.
+fn condition_ident<'a>(
+	f: &mut impl Conditions<'a>,
+	log: WherePart,
+	kind: Operator,
+	column: Ident,
+	value: WhereIdent,
+) {
+	push_log(f, log);
+
+	f.whr_mut().push(WhereOperation {
+		kind,
+		column,
+		path: None,
+		value,
+	});
+}
+
+/// Adds `column IN (SELECT ...)` or `EXISTS (SELECT ...)` if no column is
+/// given, the params of the sub query are merged into the outer ones.
+fn sub_query<'a>(
+	f: &mut impl Conditions<'a>,
+	log: WherePart,
+	column: Option<Ident>,
+	query: SubQuery,
+	params: Params<'a>,
+) {
+	push_log(f, log);
+
+	for param in params.inner {
+		f.push_where_param(param);
+	}
+
+	f.whr_mut().push(match column {
+		Some(column) => WherePart::Operation(WhereOperation {
+			kind: Operator::Any,
+			column,
+			path: None,
+			value: WhereIdent::SubQuery(Box::new(query)),
+		}),
+		None => WherePart::Exists(query),
+	});
+}
+
+fn raw<'a>(
+	f: &mut impl Conditions<'a>,
+	log: WherePart,
+	sql: SqlStr,
+	params: &[&'a (dyn ToSql + Send + Sync)],
+) {
+	for placeholder in placeholders(&sql) {
+		assert!(
+			(1..=params.len()).contains(&placeholder.num),
+			"raw sql {:?} refers to ${} but {} params were given",
+			sql,
+			placeholder.num,
+			params.len()
+		);
+	}
+
+	push_log(f, log);
+
+	for param in params {
+		f.push_where_param(Param::from_dyn("raw", *param));
+	}
+
+	f.whr_mut().push(WherePart::Raw {
+		sql,
+		params: params.len(),
+	});
+}
+
+/// Binds all values as one array param, so the sql does not depend on the
+/// number of values.
+fn condition_in<'a, T, I>(
+	f: &mut impl Conditions<'a>,
+	log: WherePart,
+	column: Ident,
+	values: I,
+) where
+	T: ParamData + ToSql + Send + Sync + 'a,
+	I: IntoIterator<Item = &'a T>,
+{
+	let values: Vec<&'a T> = values.into_iter().collect();
+
+	condition(
+		f,
+		log,
+		Operator::Any,
+		None,
+		Param::new_owned(column, values),
+	);
+}
+
+/// Combines the where clause of other with the existing one, the params are
+/// merged so they keep the order of the sql.
+fn combine<'a>(
+	f: &mut impl Conditions<'a>,
+	log: WherePart,
+	other: WhereFilter<'a>,
+) {
+	if other.whr.is_empty() {
+		return;
+	}
+
+	// AND binds stronger than OR so both sides need parentheses
+	let is_and = matches!(log, WherePart::And);
+	let whr = f.whr_mut();
+	if is_and && whr.has_or() {
+		let inner = mem::replace(whr, Where::new());
+		whr.push(WherePart::Nested(inner));
+	}
+
+	push_log(f, log);
+
+	for param in other.params.inner {
+		f.push_where_param(param);
+	}
+
+	let whr = f.whr_mut();
+	if is_and && other.whr.has_or() {
+		whr.push(WherePart::Nested(other.whr));
+	} else {
+		whr.inner.extend(other.whr.inner);
+	}
+}
+
+fn group<'a>(
+	f: &mut impl Conditions<'a>,
+	log: WherePart,
+	not: bool,
+	g: WhereFilter<'a>,
+) {
+	// a group where all conditions were skipped is ignored
+	if g.whr.is_empty() {
+		return;
+	}
+
+	push_log(f, log);
+
+	for param in g.params.inner {
+		f.push_where_param(param);
+	}
+
+	f.whr_mut().push(if not {
+		WherePart::Not(g.whr)
+	} else {
+		WherePart::Nested(g.whr)
+	});
+}
+
+macro_rules! builder_op {
+	// a typed column only accepts values of the same type
+	(
+		$and:ident, $or:ident, $and_opt:ident, $or_opt:ident,
+		$kind:ident, $symb:literal
+	) => {
+		builder_op! {
+			$and, $or, $and_opt, $or_opt, $kind, $symb,
+			[IntoColumn<T>], into_column
+		}
+	};
+	// the value is a list of values with the type of the column
+	(
+		$and:ident, $or:ident, $and_opt:ident, $or_opt:ident,
+		$kind:ident, $symb:literal, list
+	) => {
+		builder_op! {
+			$and, $or, $and_opt, $or_opt, $kind, $symb,
+			[IntoListColumn<T>], into_list_column
+		}
+	};
+	// the value has another type than the column
+	(
+		$and:ident, $or:ident, $and_opt:ident, $or_opt:ident,
+		$kind:ident, $symb:literal, untyped
+	) => {
+		builder_op! {
+			$and, $or, $and_opt, $or_opt, $kind, $symb,
+			[Into<Ident>], into
+		}
+	};
+	(
+		$and:ident, $or:ident, $and_opt:ident, $or_opt:ident,
+		$kind:ident, $symb:literal, [$($bound:tt)+], $conv:ident
+	) => {
+		#[doc = concat!("Adds `AND column ", $symb, " value`.")]
+		pub fn $and<T>(
+			mut self,
+			column: impl $($bound)+,
+			value: &'a T,
+		) -> Self
+		where
+			T: ParamData + ToSql + Send + Sync,
+		{
+			let column: Ident = column.$conv();
+			let param = Param::new(column, value);
+			condition(&mut self, WherePart::And, Operator::$kind, None, param);
+			self
+		}
+
+		#[doc = concat!("Adds `OR column ", $symb, " value`.")]
+		pub fn $or<T>(mut self, column: impl $($bound)+, value: &'a T) -> Self
+		where
+			T: ParamData + ToSql + Send + Sync,
+		{
+			let column: Ident = column.$conv();
+			let param = Param::new(column, value);
+			condition(&mut self, WherePart::Or, Operator::$kind, None, param);
+			self
+		}
+
+		#[doc = concat!("Adds `AND column ", $symb, " value` if the value")]
+		#[doc = "is `Some`."]
+		pub fn $and_opt<T>(
+			self,
+			column: impl $($bound)+,
+			value: Option<&'a T>,
+		) -> Self
+		where
+			T: ParamData + ToSql + Send + Sync,
+		{
+			match value {
+				Some(value) => self.$and(column, value),
+				None => self,
+			}
+		}
+
+		#[doc = concat!("Adds `OR column ", $symb, " value` if the value")]
+		#[doc = "is `Some`."]
+		pub fn $or_opt<T>(
+			self,
+			column: impl $($bound)+,
+			value: Option<&'a T>,
+		) -> Self
+		where
+			T: ParamData + ToSql + Send + Sync,
+		{
+			match value {
+				Some(value) => self.$or(column, value),
+				None => self,
+			}
+		}
+	};
+}
+
+macro_rules! where_builder {
+	($ty:ident) => {
+		impl<'a> $ty<'a> {
+			builder_op! { and_eq, or_eq, and_eq_opt, or_eq_opt, Eq, "=" }
+			builder_op! { and_ne, or_ne, and_ne_opt, or_ne_opt, Ne, "!=" }
+			builder_op! { and_lt, or_lt, and_lt_opt, or_lt_opt, Lt, "<" }
+			builder_op! { and_lte, or_lte, and_lte_opt, or_lte_opt, Lte, "<=" }
+			builder_op! { and_gt, or_gt, and_gt_opt, or_gt_opt, Gt, ">" }
+			builder_op! { and_gte, or_gte, and_gte_opt, or_gte_opt, Gte, ">=" }
+			builder_op! {
+				and_like, or_like, and_like_opt, or_like_opt, Like, "LIKE"
+			}
+			builder_op! {
+				and_ilike, or_ilike, and_ilike_opt, or_ilike_opt, ILike, "ILIKE"
+			}
+			builder_op! {
+				and_not_like, or_not_like, and_not_like_opt, or_not_like_opt,
+				NotLike, "NOT LIKE"
+			}
+			builder_op! {
+				and_not_ilike, or_not_ilike, and_not_ilike_opt, or_not_ilike_opt,
+				NotILike, "NOT ILIKE"
+			}
+			builder_op! {
+				and_regex, or_regex, and_regex_opt, or_regex_opt, Regex, "~"
+			}
+			builder_op! {
+				and_iregex, or_iregex, and_iregex_opt, or_iregex_opt, IRegex,
+				"~*"
+			}
+			builder_op! {
+				and_not_regex, or_not_regex, and_not_regex_opt, or_not_regex_opt,
+				NotRegex, "!~"
+			}
+			builder_op! {
+				and_not_iregex, or_not_iregex, and_not_iregex_opt,
+				or_not_iregex_opt, NotIRegex, "!~*"
+			}
+			builder_op! {
+				and_any, or_any, and_any_opt, or_any_opt, Any, "= ANY", list
+			}
+			builder_op! {
+				and_ne_all, or_ne_all, and_ne_all_opt, or_ne_all_opt, NeAll,
+				"!= ALL", list
+			}
+			builder_op! {
+				and_contains, or_contains, and_contains_opt, or_contains_opt,
+				Contains, "@>"
+			}
+			builder_op! {
+				and_contained_by, or_contained_by, and_contained_by_opt,
+				or_contained_by_opt, ContainedBy, "<@"
+			}
+			builder_op! {
+				and_overlaps, or_overlaps, and_overlaps_opt, or_overlaps_opt,
+				Overlaps, "&&"
+			}
+			builder_op! {
+				and_has_key, or_has_key, and_has_key_opt, or_has_key_opt,
+				HasKey, "?", untyped
+			}
+			builder_op! {
+				and_similar_to, or_similar_to, and_similar_to_opt,
+				or_similar_to_opt, SimilarTo, "SIMILAR TO"
+			}
+
+			builder_op! {
+				and_similar, or_similar, and_similar_opt, or_similar_opt,
+				Similar, "%"
+			}
+			builder_op! {
+				and_word_similar, or_word_similar, and_word_similar_opt,
+				or_word_similar_opt, WordSimilar, "%>"
+			}
+
+			/// Adds `AND similarity(column, value) > threshold`.
+			pub fn and_similarity<T>(
+				mut self,
+				column: impl IntoColumn<T>,
+				value: &'a T,
+				threshold: f64,
+			) -> Self
+			where
+				T: ParamData + ToSql + Send + Sync,
+			{
+				let param = Param::new(column, value);
+				let kind = Operator::Similarity { threshold };
+				condition(&mut self, WherePart::And, kind, None, param);
+				self
+			}
+
+			/// Adds `OR similarity(column, value) > threshold`.
+			pub fn or_similarity<T>(
+				mut self,
+				column: impl IntoColumn<T>,
+				value: &'a T,
+				threshold: f64,
+			) -> Self
+			where
+				T: ParamData + ToSql + Send + Sync,
+			{
+				let param = Param::new(column, value);
+				let kind = Operator::Similarity { threshold };
+				condition(&mut self, WherePart::Or, kind, None, param);
+				self
+			}
+
+			/// Adds `AND word_similarity(value, column) > threshold`.
+			pub fn and_word_similarity<T>(
+				mut self,
+				column: impl IntoColumn<T>,
+				value: &'a T,
+				threshold: f64,
+			) -> Self
+			where
+				T: ParamData + ToSql + Send + Sync,
+			{
+				let param = Param::new(column, value);
+				let kind = Operator::WordSimilarity { threshold };
+				condition(&mut self, WherePart::And, kind, None, param);
+				self
+			}
+
+			/// Adds `OR word_similarity(value, column) > threshold`.
+			pub fn or_word_similarity<T>(
+				mut self,
+				column: impl IntoColumn<T>,
+				value: &'a T,
+				threshold: f64,
+			) -> Self
+			where
+				T: ParamData + ToSql + Send + Sync,
+			{
+				let param = Param::new(column, value);
+				let kind = Operator::WordSimilarity { threshold };
+				condition(&mut self, WherePart::Or, kind, None, param);
+				self
+			}
+
+			/// Adds `AND column = ANY(values)`.
+			pub fn and_in<T, I>(
+				mut self,
+				column: impl IntoColumn<T>,
+				values: I,
+			) -> Self
+			where
+				T: ParamData + ToSql + Send + Sync + 'a,
+				I: IntoIterator<Item = &'a T>,
+			{
+				let column = column.into_column();
+				condition_in(&mut self, WherePart::And, column, values);
+				self
+			}
+
+			/// Adds `OR column = ANY(values)`.
+			pub fn or_in<T, I>(
+				mut self,
+				column: impl IntoColumn<T>,
+				values: I,
+			) -> Self
+			where
+				T: ParamData + ToSql + Send + Sync + 'a,
+				I: IntoIterator<Item = &'a T>,
+			{
+				let column = column.into_column();
+				condition_in(&mut self, WherePart::Or, column, values);
+				self
+			}
+
+			/// Adds `AND column<path> operator value`.
+			///
+			/// Paths ending with `->>` or `#>>` need a text value, all others
+			/// a json value.
+			pub fn and_json<T>(
+				mut self,
+				column: impl Into<Ident>,
+				path: JsonPath,
+				kind: JsonComparison,
+				value: &'a T,
+			) -> Self
+			where
+				T: ParamData + ToSql + Send + Sync,
+			{
+				let param = Param::new(column.into(), value);
+				let kind = kind.into();
+				condition(&mut self, WherePart::And, kind, Some(path), param);
+				self
+			}
+
+			/// Adds `OR column<path> operator value`.
+			///
+			/// Paths ending with `->>` or `#>>` need a text value, all others
+			/// a json value.
+			pub fn or_json<T>(
+				mut self,
+				column: impl Into<Ident>,
+				path: JsonPath,
+				kind: JsonComparison,
+				value: &'a T,
+			) -> Self
+			where
+				T: ParamData + ToSql + Send + Sync,
+			{
+				let param = Param::new(column.into(), value);
+				let kind = kind.into();
+				condition(&mut self, WherePart::Or, kind, Some(path), param);
+				self
+			}
+
+			/// Adds `AND column @@ query(value)`, the column needs to be a
+			/// tsvector.
+			pub fn and_search<T>(
+				mut self,
+				column: impl Into<Ident>,
+				search: TextSearch,
+				value: &'a T,
+			) -> Self
+			where
+				T: ParamData + ToSql + Send + Sync,
+			{
+				let param = Param::new(column.into(), value);
+				let kind = Operator::TextSearch(search);
+				condition(&mut self, WherePart::And, kind, None, param);
+				self
+			}
+
+			/// Adds `OR column @@ query(value)`, the column needs to be a
+			/// tsvector.
+			pub fn or_search<T>(
+				mut self,
+				column: impl Into<Ident>,
+				search: TextSearch,
+				value: &'a T,
+			) -> Self
+			where
+				T: ParamData + ToSql + Send + Sync,
+			{
+				let param = Param::new(column.into(), value);
+				let kind = Operator::TextSearch(search);
+				condition(&mut self, WherePart::Or, kind, None, param);
+				self
+			}
+
+			/// Adds `AND column @@ query(value)` if the value is `Some`.
+			pub fn and_search_opt<T>(
+				self,
+				column: impl Into<Ident>,
+				search: TextSearch,
+				value: Option<&'a T>,
+			) -> Self
+			where
+				T: ParamData + ToSql + Send + Sync,
+			{
+				match value {
+					Some(value) => self.and_search(column, search, value),
+					None => self,
+				}
+			}
+
+			/// Adds `OR column @@ query(value)` if the value is `Some`.
+			pub fn or_search_opt<T>(
+				self,
+				column: impl Into<Ident>,
+				search: TextSearch,
+				value: Option<&'a T>,
+			) -> Self
+			where
+				T: ParamData + ToSql + Send + Sync,
+			{
+				match value {
+					Some(value) => self.or_search(column, search, value),
+					None => self,
+				}
+			}
+
+			/// Adds `AND column operator other_column`.
+			pub fn and_column(
+				mut self,
+				column: impl Into<Ident>,
+				kind: Comparison,
+				other: impl Into<Ident>,
+			) -> Self {
+				let other = WhereIdent::Name(other.into());
+				let (log, column) = (WherePart::And, column.into());
+				condition_ident(&mut self, log, kind.into(), column, other);
+				self
+			}
+
+			/// Adds `OR column operator other_column`.
+			pub fn or_column(
+				mut self,
+				column: impl Into<Ident>,
+				kind: Comparison,
+				other: impl Into<Ident>,
+			) -> Self {
+				let other = WhereIdent::Name(other.into());
+				let (log, column) = (WherePart::Or, column.into());
+				condition_ident(&mut self, log, kind.into(), column, other);
+				self
+			}
+
+			/// Adds `AND column operator sql`, for example `now()`.
+			///
+			/// The sql is not escaped, never pass user input.
+			pub fn and_sql(
+				mut self,
+				column: impl Into<Ident>,
+				kind: Comparison,
+				sql: impl Into<SqlStr>,
+			) -> Self {
+				let sql = WhereIdent::Sql(sql.into());
+				let (log, column) = (WherePart::And, column.into());
+				condition_ident(&mut self, log, kind.into(), column, sql);
+				self
+			}
+
+			/// Adds `OR column operator sql`, for example `now()`.
+			///
+			/// The sql is not escaped, never pass user input.
+			pub fn or_sql(
+				mut self,
+				column: impl Into<Ident>,
+				kind: Comparison,
+				sql: impl Into<SqlStr>,
+			) -> Self {
+				let sql = WhereIdent::Sql(sql.into());
+				let (log, column) = (WherePart::Or, column.into());
+				condition_ident(&mut self, log, kind.into(), column, sql);
+				self
+			}
+
+			/// Adds `AND (sql)`, `$1` to `$n` refer to the given params and get
+			/// renumbered.
+			///
+			/// The sql is not escaped, never pass user input.
+			///
+			/// ## Panics
+			/// If a `$n` outside of quotes is not between `$1` and the number
+			/// of params.
+			pub fn and_raw_unchecked(
+				mut self,
+				sql: impl Into<SqlStr>,
+				params: &[&'a (dyn ToSql + Send + Sync)],
+			) -> Self {
+				raw(&mut self, WherePart::And, sql.into(), params);
+				self
+			}
+
+			/// Adds `OR (sql)`, `$1` to `$n` refer to the given params and get
+			/// renumbered.
+			///
+			/// The sql is not escaped, never pass user input.
+			///
+			/// ## Panics
+			/// If a `$n` outside of quotes is not between `$1` and the number
+			/// of params.
+			pub fn or_raw_unchecked(
+				mut self,
+				sql: impl Into<SqlStr>,
+				params: &[&'a (dyn ToSql + Send + Sync)],
+			) -> Self {
+				raw(&mut self, WherePart::Or, sql.into(), params);
+				self
+			}
+
+			/// Adds `AND column IN (SELECT select FROM table WHERE ...)`.
+			pub fn and_in_select(
+				mut self,
+				column: impl Into<Ident>,
+				table: impl Into<Ident>,
+				select: impl Into<Ident>,
+				filter: WhereFilter<'a>,
+			) -> Self {
+				let query =
+					SubQuery::new(table, Some(select.into()), filter.whr);
+				let column = Some(column.into());
+				let log = WherePart::And;
+				sub_query(&mut self, log, column, query, filter.params);
+				self
+			}
+
+			/// Adds `OR column IN (SELECT select FROM table WHERE ...)`.
+			pub fn or_in_select(
+				mut self,
+				column: impl Into<Ident>,
+				table: impl Into<Ident>,
+				select: impl Into<Ident>,
+				filter: WhereFilter<'a>,
+			) -> Self {
+				let query =
+					SubQuery::new(table, Some(select.into()), filter.whr);
+				let column = Some(column.into());
+				let log = WherePart::Or;
+				sub_query(&mut self, log, column, query, filter.params);
+				self
+			}
+
+			/// Adds `AND EXISTS (SELECT 1 FROM table WHERE ...)`.
+			pub fn and_exists(
+				mut self,
+				table: impl Into<Ident>,
+				filter: WhereFilter<'a>,
+			) -> Self {
+				let query = SubQuery::new(table, None, filter.whr);
+				let log = WherePart::And;
+				sub_query(&mut self, log, None, query, filter.params);
+				self
+			}
+
+			/// Adds `OR EXISTS (SELECT 1 FROM table WHERE ...)`.
+			pub fn or_exists(
+				mut self,
+				table: impl Into<Ident>,
+				filter: WhereFilter<'a>,
+			) -> Self {
+				let query = SubQuery::new(table, None, filter.whr);
+				let log = WherePart::Or;
+				sub_query(&mut self, log, None, query, filter.params);
+				self
+			}
+
+			/// Combines both clauses with `AND`, parentheses are added where
+			/// needed and the params are renumbered.
+			pub fn and(mut self, other: WhereFilter<'a>) -> Self {
+				combine(&mut self, WherePart::And, other);
+				self
+			}
+
+			/// Combines both clauses with `OR`, the params are renumbered.
+			pub fn or(mut self, other: WhereFilter<'a>) -> Self {
+				combine(&mut self, WherePart::Or, other);
+				self
+			}
+
+			/// Adds `AND (...)`, the group is ignored if it is empty.
+			pub fn and_group(
+				mut self,
+				f: impl FnOnce(WhereFilter<'a>) -> WhereFilter<'a>,
+			) -> Self {
+				group(&mut self, WherePart::And, false, f(WhereFilter::new()));
+				self
+			}
+
+			/// Adds `OR (...)`, the group is ignored if it is empty.
+			pub fn or_group(
+				mut self,
+				f: impl FnOnce(WhereFilter<'a>) -> WhereFilter<'a>,
+			) -> Self {
+				group(&mut self, WherePart::Or, false, f(WhereFilter::new()));
+				self
+			}
+
+			/// Adds `AND NOT (...)`, the group is ignored if it is empty.
+			pub fn and_not(
+				mut self,
+				f: impl FnOnce(WhereFilter<'a>) -> WhereFilter<'a>,
+			) -> Self {
+				group(&mut self, WherePart::And, true, f(WhereFilter::new()));
+				self
+			}
+
+			/// Adds `OR NOT (...)`, the group is ignored if it is empty.
+			pub fn or_not(
+				mut self,
+				f: impl FnOnce(WhereFilter<'a>) -> WhereFilter<'a>,
+			) -> Self {
+				group(&mut self, WherePart::Or, true, f(WhereFilter::new()));
+				self
+			}
+		}
+	};
+}
+
+where_builder!(Filter);
+where_builder!(WhereFilter);
+
+impl<'a> Filter<'a> {
+	/// Creates a filter without ordering or limit from a where clause.
+	pub fn from_where(whr: WhereFilter<'a>) -> Self {
+		let mut filter = Self::new();
+		filter.whr = whr.whr;
+		filter.params = whr.params;
+		filter
+	}
+
+	/// Adds `AND column operator param`, the param can own its value.
+	pub(crate) fn and_param(
+		mut self,
+		kind: Operator,
+		param: Param<'a>,
+	) -> Self {
+		condition(&mut self, WherePart::And, kind, None, param);
+		self
+	}
+
+	pub fn order_asc(mut self, column: impl Into<Ident>) -> Self {
+		self.order_by.push_asc(column);
+		self
+	}
+
+	pub fn order_desc(mut self, column: impl Into<Ident>) -> Self {
+		self.order_by.push_desc(column);
+		self
+	}
+
+	/// Orders by a column with a direction which is only known at runtime.
+	pub fn order(
+		mut self,
+		column: impl Into<Ident>,
+		direction: Direction,
+	) -> Self {
+		self.order_by.push(OrderByPart::column(column, direction));
+		self
+	}
+
+	/// Adds a part to the order by, for example an expression or a part with
+	/// `NULLS FIRST`.
+	///
+	/// Parts which need a param can only be added with
+	/// [`Filter::order_rank`] and [`Filter::order_distance`].
+	pub fn order_by(mut self, part: OrderByPart) -> Self {
+		self.order_by.push(part);
+		self
+	}
+
+	/// Orders by `ts_rank(column, query(value))`, the most relevant rows
+	/// first.
+	pub fn order_rank<T>(
+		mut self,
+		column: impl Into<Ident>,
+		search: TextSearch,
+		value: &'a T,
+	) -> Self
+	where
+		T: ParamData + ToSql + Send + Sync,
+	{
+		let column = column.into();
+		let index = self.params.len() - limit_offset_params(&self);
+		self.params.insert(index, Param::new(column.clone(), value));
+		self.order_by.push_rank(column, search);
+		self
+	}
+
+	/// Orders by the trigram distance `column <-> value`, the most similar
+	/// rows first.
+	pub fn order_distance<T>(
+		mut self,
+		column: impl IntoColumn<T>,
+		value: &'a T,
+	) -> Self
+	where
+		T: ParamData + ToSql + Send + Sync,
+	{
+		let column = column.into_column();
+		let index = self.params.len() - limit_offset_params(&self);
+		self.params.insert(index, Param::new(column.clone(), value));
+		self.order_by.push_distance(column);
+		self
+	}
+
+	/// Only matches the rows after the cursor, the cursor needs to come from
+	/// a page with the same order by.
+	///
+	/// See [`Connection::select_keyset`](crate::Connection::select_keyset).
+	pub fn after(mut self, cursor: &'a Cursor) -> Result<Self, CursorError> {
+		let keyset = Keyset::new(&self.order_by)?;
+		if keyset.param_count() != cursor.len() {
+			return Err(CursorError::Mismatch {
+				expected: keyset.param_count(),
+				found: cursor.len(),
+			});
+		}
+
+		let mut g = WhereFilter::new();
+		for (col, value) in keyset.columns.iter().zip(cursor.values()) {
+			g.params.push(Param::new(col.name.clone(), value));
+		}
+		g.whr.push(WherePart::Keyset(keyset));
+
+		combine(&mut self, WherePart::And, g);
+		Ok(self)
+	}
+
+	pub fn limit(mut self, limit: usize) -> Self {
+		self.limit.set_fixed(limit);
+		self
+	}
+
+	pub fn offset(mut self, offset: usize) -> Self {
+		self.offset.set_fixed(offset);
+		self
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_builder() {
+		let status = "active";
+		let created = 10;
+		let name: Option<String> = None;
+		let ids = [1, 2];
+
+		let filter = Filter::new()
+			.and_eq("status", &status)
+			.and_gt("created", &created)
+			.and_like_opt("name", name.as_ref())
+			.or_group(|g| g.and_in("id", &ids).and_eq("deleted", &name))
+			.and_not(|g| g.and_eq_opt("name", name.as_ref()))
+			.order_desc("created")
+			.limit(20);
+
+		assert_eq!(
+			filter.to_string(),
+			" WHERE \"status\" = $1 AND \"created\" > $2 OR \
+			(\"id\" = ANY($3) AND \"deleted\" IS NULL) \
+			ORDER BY \"created\" DESC LIMIT 20"
+		);
+		assert_eq!(filter.params.len(), 3);
+	}
+
+	#[test]
+	fn test_builder_json() {
+		let plan = "pro";
+		let key = "beta";
+
+		let filter = WhereFilter::new()
+			.and_json(
+				"meta",
+				JsonPath::new().get_text("plan"),
+				JsonComparison::Eq,
+				&plan,
+			)
+			.or_has_key("meta", &key);
+
+		assert_eq!(
+			filter.to_string(),
+			" WHERE \"meta\"->>'plan' = $1 OR \"meta\" ? $2"
+		);
+	}
+
+	#[test]
+	fn test_builder_search() {
+		let q = "rust postgres";
+		let limit = &10;
+
+		let filter = crate::filter!(LIMIT limit)
+			.order_rank("search", TextSearch::websearch(), &q)
+			.and_eq("lang", &"en")
+			.and_search("search", TextSearch::plain().language("english"), &q);
+
+		assert_eq!(
+			filter.to_string(),
+			" WHERE \"lang\" = $1 AND \"search\" @@ \
+			plainto_tsquery('english', $2) \
+			ORDER BY ts_rank(\"search\", websearch_to_tsquery($3)) DESC \
+			LIMIT $4"
+		);
+		assert_eq!(filter.params.len(), 4);
+	}
+
+	#[test]
+	fn test_builder_trigram() {
+		let name = "jon";
+
+		let filter = Filter::new()
+			.and_similar("name", &name)
+			.or_word_similarity("name", &name, 0.4)
+			.order_distance("name", &name)
+			.limit(10);
+
+		assert_eq!(
+			filter.to_string(),
+			" WHERE \"name\" % $1 OR word_similarity($2, \"name\") > 0.4 \
+			ORDER BY \"name\" <-> $3 ASC LIMIT 10"
+		);
+	}
+
+	#[test]
+	fn test_builder_raw() {
+		let a = 1;
+		let b = "b";
+
+		let filter = Filter::new()
+			.and_eq("a", &a)
+			.and_column("updated", Comparison::Gt, "created")
+			.or_sql("expires", Comparison::Lt, "now()")
+			.and_raw_unchecked("lower(\"b\") = $1 OR $2 > 3", &[&b, &a])
+			.and_eq("c", &a);
+
+		assert_eq!(
+			filter.to_string(),
+			" WHERE \"a\" = $1 AND \"updated\" > \"created\" OR \
+			\"expires\" < now() AND (lower(\"b\") = $2 OR $3 > 3) AND \
+			\"c\" = $4"
+		);
+		assert_eq!(filter.params.len(), 4);
+	}
+
+	#[test]
+	fn test_builder_raw_quotes() {
+		let a = 1;
+
+		let filter = Filter::new().and_eq("a", &a).and_raw_unchecked(
+			"\"b$1\" = '$2''$3' AND $1 > $tag$ $4 $tag$::int",
+			&[&a],
+		);
+
+		assert_eq!(
+			filter.to_string(),
+			" WHERE \"a\" = $1 AND (\"b$1\" = '$2''$3' AND $2 > \
+			$tag$ $4 $tag$::int)"
+		);
+	}
+
+	#[test]
+	#[should_panic(expected = "refers to $2 but 1 params were given")]
+	fn test_builder_raw_too_few_params() {
+		let a = 1;
+		let _ = Filter::new().and_raw_unchecked("$1 = $2", &[&a]);
+	}
+
+	#[test]
+	#[should_panic(expected = "refers to $0")]
+	fn test_builder_raw_zero() {
+		let a = 1;
+		let _ = WhereFilter::new().or_raw_unchecked("$0 = 1", &[&a]);
+	}
+
+	#[test]
+	fn test_builder_sub_query() {
+		let a = 1;
+		let active = true;
+
+		let filter = Filter::new()
+			.and_eq("a", &a)
+			.and_in_select(
+				"user_id",
+				"users",
+				"id",
+				WhereFilter::new().and_eq("active", &active),
+			)
+			.or_not(|g| g.and_exists("bans", WhereFilter::new()))
+			.and_eq("b", &a)
+			.limit(1);
+
+		assert_eq!(
+			filter.to_string(),
+			" WHERE \"a\" = $1 AND \"user_id\" = ANY(SELECT \"id\" FROM \
+			\"users\" WHERE \"active\" = $2) OR NOT (EXISTS (SELECT 1 FROM \
+			\"bans\")) AND \"b\" = $3 LIMIT 1"
+		);
+		assert_eq!(filter.params.len(), 3);
+	}
+
+	#[test]
+	fn test_combine() {
+		let a = 1;
+		let b = 2;
+		let tenant = 3;
+		let limit = &10;
+
+		let filter = crate::filter!("a" = &a OR "b" = &b LIMIT limit)
+			.and(crate::whr!("tenant" = &tenant));
+		assert_eq!(
+			filter.to_string(),
+			" WHERE (\"a\" = $1 OR \"b\" = $2) AND \"tenant\" = $3 \
+			LIMIT $4"
+		);
+		assert_eq!(filter.params.len(), 4);
+
+		let whr = crate::whr!("a" = &a AND "b" = &b)
+			.or(crate::whr!("a" = &b OR "b" = &a))
+			.and(crate::whr!("c" = &a OR "d" = &a))
+			.or(WhereFilter::new());
+		assert_eq!(
+			whr.to_string(),
+			" WHERE (\"a\" = $1 AND \"b\" = $2 OR \"a\" = $3 OR \
+			\"b\" = $4) AND (\"c\" = $5 OR \"d\" = $6)"
+		);
+
+		// an OR in a raw fragment stays inside of its parentheses
+		let filter = Filter::new()
+			.and_raw_unchecked("\"a\" = $1 OR true", &[&a])
+			.and(crate::whr!("tenant" = &tenant));
+		assert_eq!(
+			filter.to_string(),
+			" WHERE (\"a\" = $1 OR true) AND \"tenant\" = $2"
+		);
+
+		let whr = WhereFilter::new()
+			.and_eq("tenant", &tenant)
+			.and(WhereFilter::new().or_raw_unchecked("\"a\" = 1 OR true", &[]));
+		assert_eq!(
+			whr.to_string(),
+			" WHERE \"tenant\" = $1 AND (\"a\" = 1 OR true)"
+		);
+
+		let filter = Filter::from_where(crate::whr!("a" = &a))
+			.or(crate::whr!("b" = &b))
+			.order_asc("a");
+		assert_eq!(
+			filter.to_string(),
+			" WHERE \"a\" = $1 OR \"b\" = $2 ORDER BY \"a\" ASC"
+		);
+	}
+
+	#[test]
+	fn test_builder_after_limit() {
+		let a = 1;
+		let limit = &10;
+
+		let mut filter = crate::filter!(LIMIT limit);
+		filter = filter.and_eq("a", &a);
+
+		assert_eq!(filter.to_string(), " WHERE \"a\" = $1 LIMIT $2");
+	}
+
+	#[test]
+	fn test_builder_escapes_names() {
+		let a = 1;
+		let column = String::from(r#"a" = 1 OR "b"#);
+
+		let filter = Filter::new()
+			.and_eq(column.clone(), &a)
+			.and_column("b", Comparison::Eq, column.clone())
+			.order_desc(column);
+		assert_eq!(
+			filter.to_string(),
+			r#" WHERE "a"" = 1 OR ""b" = $1 AND "b" = "a"" = 1 OR ""b" ORDER BY "a"" = 1 OR ""b" DESC"#
+		);
+	}
+}
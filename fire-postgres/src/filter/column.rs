@@ -0,0 +1,232 @@
+use std::fmt;
+use std::marker::PhantomData;
+
+use super::{ParamData, SqlStr, TsVector};
+use crate::Ident;
+
+/// A column with its name and rust type.
+///
+/// With `#[row(columns)]` the [`FromRow`](crate::FromRow) derive creates an
+/// associated constant for every field and the
+/// [`TableTempl`](crate::TableTempl) derive one for every `text_search`
+/// column, filters then check the type of the value at compile time. The lists of `IN`, `= ANY` and `!= ALL` need elements of the same
+/// type.
+///
+/// ## Example
+/// ```
+/// # use fire_postgres::{macros, row};
+/// use fire_postgres::{filter, FromRow};
+///
+/// #[derive(FromRow)]
+/// #[row(columns)]
+/// struct User {
+/// 	email: String,
+/// 	age: Option<i32>,
+/// }
+///
+/// # fn main() {
+/// let email = "a@b.c";
+/// let age = 3;
+/// let filter = filter!(User::EMAIL = &email AND User::AGE > &age);
+/// assert_eq!(filter.to_string(), r#" WHERE "email" = $1 AND "age" > $2"#);
+///
+/// let ages = vec![1, 2];
+/// let filter = filter!(User::AGE IN &ages OR User::AGE = ANY &ages);
+/// assert_eq!(
+/// 	filter.to_string(),
+/// 	r#" WHERE "age" = ANY($1) OR "age" = ANY($2)"#
+/// );
+/// # }
+/// ```
+///
+/// Comparing a column with a value of another type does not compile.
+/// ```compile_fail
+/// # use fire_postgres::{macros, row};
+/// use fire_postgres::{filter, FromRow};
+///
+/// #[derive(FromRow)]
+/// #[row(columns)]
+/// struct User {
+/// 	age: i32,
+/// }
+///
+/// # fn main() {
+/// let age = "3";
+/// let filter = filter!(User::AGE = &age);
+/// # }
+/// ```
+///
+/// The same goes for the elements of a list.
+/// ```compile_fail
+/// # use fire_postgres::{macros, row};
+/// use fire_postgres::{filter, FromRow};
+///
+/// #[derive(FromRow)]
+/// #[row(columns)]
+/// struct User {
+/// 	age: i32,
+/// }
+///
+/// # fn main() {
+/// let ages = vec!["3"];
+/// let filter = filter!(User::AGE IN &ages);
+/// # }
+/// ```
+pub struct Col<T: ?Sized> {
+	name: &'static str,
+	ty: PhantomData<fn() -> T>,
+}
+
+impl<T: ?Sized> Col<T> {
+	pub const fn new(name: &'static str) -> Self {
+		Self {
+			name,
+			ty: PhantomData,
+		}
+	}
+
+	pub const fn name(&self) -> &'static str {
+		self.name
+	}
+}
+
+impl<T: ?Sized> Clone for Col<T> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
+impl<T: ?Sized> Copy for Col<T> {}
+
+impl<T: ?Sized> fmt::Debug for Col<T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_tuple("Col").field(&self.name).finish()
+	}
+}
+
+impl<T: ?Sized> From<Col<T>> for Ident {
+	fn from(col: Col<T>) -> Self {
+		Ident::new(col.name)
+	}
+}
+
+/// Values of this type can be compared with a column of type `T`.
+pub trait ParamFor<T: ?Sized> {}
+
+impl<T: ParamData + ?Sized> ParamFor<T> for T {}
+impl<T: ParamData + ?Sized> ParamFor<T> for &T {}
+impl<T: ParamData> ParamFor<Option<T>> for T {}
+impl ParamFor<String> for str {}
+impl ParamFor<String> for &str {}
+impl ParamFor<Option<String>> for &str {}
+impl ParamFor<TsVector> for str {}
+impl ParamFor<TsVector> for &str {}
+impl ParamFor<TsVector> for String {}
+
+/// Lists of this type have elements which can be compared with a column of
+/// type `T`.
+pub trait ListFor<T: ?Sized> {}
+
+impl<T: ?Sized, E: ParamFor<T>> ListFor<T> for Vec<E> {}
+impl<T: ?Sized, E: ParamFor<T>> ListFor<T> for [E] {}
+impl<T: ?Sized, E: ParamFor<T>, const N: usize> ListFor<T> for [E; N] {}
+impl<T: ?Sized, L: ListFor<T> + ?Sized> ListFor<T> for &L {}
+
+/// A column which can be compared with values of type `V`.
+///
+/// Strings accept any value, a [`Col`] only values which implement
+/// [`ParamFor`].
+pub trait IntoColumn<V: ?Sized> {
+	fn into_column(self) -> Ident;
+}
+
+impl<V: ?Sized> IntoColumn<V> for &'static str {
+	fn into_column(self) -> Ident {
+		self.into()
+	}
+}
+
+impl<V: ?Sized> IntoColumn<V> for String {
+	fn into_column(self) -> Ident {
+		self.into()
+	}
+}
+
+impl<V: ?Sized> IntoColumn<V> for SqlStr {
+	fn into_column(self) -> Ident {
+		self.into()
+	}
+}
+
+impl<V: ?Sized> IntoColumn<V> for Ident {
+	fn into_column(self) -> Ident {
+		self
+	}
+}
+
+impl<V: ?Sized> IntoColumn<V> for &Ident {
+	fn into_column(self) -> Ident {
+		self.clone()
+	}
+}
+
+impl<T: ?Sized, V: ParamFor<T> + ?Sized> IntoColumn<V> for Col<T> {
+	fn into_column(self) -> Ident {
+		self.into()
+	}
+}
+
+/// A column which can be compared with the elements of a list of type `V`,
+/// used by `= ANY` and `!= ALL`.
+///
+/// Strings accept any list, a [`Col`] only lists which implement
+/// [`ListFor`].
+pub trait IntoListColumn<V: ?Sized> {
+	fn into_list_column(self) -> Ident;
+}
+
+impl<V: ?Sized> IntoListColumn<V> for &'static str {
+	fn into_list_column(self) -> Ident {
+		self.into()
+	}
+}
+
+impl<V: ?Sized> IntoListColumn<V> for String {
+	fn into_list_column(self) -> Ident {
+		self.into()
+	}
+}
+
+impl<V: ?Sized> IntoListColumn<V> for SqlStr {
+	fn into_list_column(self) -> Ident {
+		self.into()
+	}
+}
+
+impl<V: ?Sized> IntoListColumn<V> for Ident {
+	fn into_list_column(self) -> Ident {
+		self
+	}
+}
+
+impl<V: ?Sized> IntoListColumn<V> for &Ident {
+	fn into_list_column(self) -> Ident {
+		self.clone()
+	}
+}
+
+impl<T: ?Sized, V: ListFor<T> + ?Sized> IntoListColumn<V> for Col<T> {
+	fn into_list_column(self) -> Ident {
+		self.into()
+	}
+}
+
+/// Checks the type of the list against the column, used by the `IN` of the
+/// macros which collects the values before binding them.
+#[doc(hidden)]
+pub fn list_column<V: ?Sized>(
+	column: impl IntoListColumn<V>,
+	_values: &V,
+) -> Ident {
+	column.into_list_column()
+}
@@ -0,0 +1,454 @@
+use std::error::Error as StdError;
+use std::fmt::Write;
+
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+use fallible_iterator::FallibleIterator;
+use postgres_protocol::types as pg;
+use postgres_types::{FromSql, Kind, ToSql, Type};
+
+use super::{placeholders, Aggregates, Filter, Params, WhereFilter};
+use crate::connection::{
+	aggregate_sql, delete_sql, select_columns_sql, select_sql, update_sql,
+};
+use crate::row::{parse_columns, RawValue};
+use crate::row::{NamedColumns, ToRow};
+use crate::{Error, Ident};
+
+type BoxError = Box<dyn StdError + Sync + Send>;
+type RedactHook = Box<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// What a redacted param is replaced with.
+const REDACTED: &str = "'<redacted>'";
+
+/// Renders sql with the params inlined as escaped literals.
+///
+/// This is only meant for debugging, logs or tools like `EXPLAIN`, the
+/// statements which are sent to postgres always use params. The params of
+/// redacted columns are replaced with `'<redacted>'`.
+///
+/// ## Example
+/// ```
+/// use fire_postgres::filter::{DebugSql, Filter};
+///
+/// let name = "O'Brien";
+/// let password = "secret";
+/// let filter = Filter::new()
+/// 	.and_eq("name", &name)
+/// 	.and_eq("password", &password)
+/// 	.limit(10);
+///
+/// assert_eq!(
+/// 	filter.to_debug_sql(),
+/// 	r#" WHERE "name" = 'O''Brien' AND "password" = 'secret' LIMIT 10"#
+/// );
+///
+/// let debug = DebugSql::new().redact("password");
+/// assert_eq!(
+/// 	debug.filter(&filter),
+/// 	r#" WHERE "name" = 'O''Brien' AND "password" = '<redacted>' LIMIT 10"#
+/// );
+/// ```
+#[derive(Default)]
+pub struct DebugSql {
+	columns: Vec<Ident>,
+	hook: Option<RedactHook>,
+}
+
+impl DebugSql {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Redacts the params of a column.
+	pub fn redact(mut self, column: impl Into<Ident>) -> Self {
+		self.columns.push(column.into());
+		self
+	}
+
+	/// Redacts the params of all columns for which the hook returns true.
+	pub fn redact_with<F>(mut self, hook: F) -> Self
+	where
+		F: Fn(&str) -> bool + Send + Sync + 'static,
+	{
+		self.hook = Some(Box::new(hook));
+		self
+	}
+
+	/// Renders the filter starting with ` WHERE`.
+	pub fn filter(&self, filter: &Filter) -> String {
+		self.inline(&filter.to_string(), params(&filter.params))
+	}
+
+	/// Renders the where clause starting with ` WHERE`.
+	pub fn where_filter(&self, filter: &WhereFilter) -> String {
+		self.inline(&filter.to_string(), params(&filter.params))
+	}
+
+	/// Renders the statement of [`Connection::select`].
+	///
+	/// [`Connection::select`]: crate::Connection::select
+	pub fn select<R>(&self, table: impl Into<Ident>, filter: &Filter) -> String
+	where
+		R: NamedColumns,
+	{
+		let sql = select_sql::<R>(&table.into(), filter);
+		self.inline(&sql, params(&filter.params))
+	}
+
+	/// Renders the statement of [`Connection::select_columns`].
+	///
+	/// [`Connection::select_columns`]: crate::Connection::select_columns
+	///
+	/// Returns an error if no columns are given.
+	pub fn select_columns<C>(
+		&self,
+		table: impl Into<Ident>,
+		columns: &[C],
+		filter: &Filter,
+	) -> Result<String, Error>
+	where
+		C: AsRef<str>,
+	{
+		let sql = select_columns_sql(&table.into(), columns, filter)?;
+		Ok(self.inline(&sql, params(&filter.params)))
+	}
+
+	/// Renders the statement of [`Connection::aggregate`].
+	///
+	/// [`Connection::aggregate`]: crate::Connection::aggregate
+	pub fn aggregate(
+		&self,
+		table: impl Into<Ident>,
+		aggregates: &Aggregates,
+		filter: &Filter,
+	) -> String {
+		let sql = aggregate_sql(&table.into(), aggregates, filter);
+		let params = aggregates
+			.params(filter)
+			.map(|p| (p.name.as_str(), p.data.as_ref()));
+		self.inline(&sql, params)
+	}
+
+	/// Renders the statement of [`Connection::update`].
+	///
+	/// [`Connection::update`]: crate::Connection::update
+	pub fn update<U>(
+		&self,
+		table: impl Into<Ident>,
+		item: &U,
+		filter: &WhereFilter,
+	) -> String
+	where
+		U: ToRow + ?Sized,
+	{
+		let sql = update_sql(&table.into(), item, &filter.whr);
+
+		let mut columns = String::new();
+		item.insert_columns(&mut columns);
+		let mut params: Vec<_> = parse_columns(&columns)
+			.into_iter()
+			.zip(item.params())
+			.collect();
+		params.extend(
+			self::params(&filter.params).map(|(c, v)| (c.to_string(), v)),
+		);
+
+		self.inline(&sql, params)
+	}
+
+	/// Renders the statement of [`Connection::delete`].
+	///
+	/// [`Connection::delete`]: crate::Connection::delete
+	pub fn delete(
+		&self,
+		table: impl Into<Ident>,
+		filter: &WhereFilter,
+	) -> String {
+		let sql = delete_sql(&table.into(), &filter.whr);
+		self.inline(&sql, params(&filter.params))
+	}
+
+	fn is_redacted(&self, column: &str) -> bool {
+		self.columns.iter().any(|c| c == column)
+			|| self.hook.as_ref().is_some_and(|hook| hook(column))
+	}
+
+	/// Replaces every `$n` outside of quotes with the literal of the param.
+	fn inline<'a, S>(
+		&self,
+		sql: &str,
+		params: impl IntoIterator<Item = (S, &'a (dyn ToSql + Sync))>,
+	) -> String
+	where
+		S: AsRef<str>,
+	{
+		let literals: Vec<_> = params
+			.into_iter()
+			.map(|(column, value)| {
+				if self.is_redacted(column.as_ref()) {
+					Some(REDACTED.to_string())
+				} else {
+					literal(value).ok()
+				}
+			})
+			.collect();
+
+		let mut s = String::with_capacity(sql.len());
+		let mut last = 0;
+		for placeholder in placeholders(sql) {
+			let literal = placeholder
+				.num
+				.checked_sub(1)
+				.and_then(|i| literals.get(i))
+				.and_then(Option::as_ref);
+
+			// params which could not be converted stay as they are
+			match literal {
+				Some(literal) => {
+					s.push_str(&sql[last..placeholder.start]);
+					s.push_str(literal);
+				}
+				None => s.push_str(&sql[last..placeholder.end]),
+			}
+			last = placeholder.end;
+		}
+		s.push_str(&sql[last..]);
+
+		s
+	}
+}
+
+impl Filter<'_> {
+	/// Renders the filter with the params inlined, see [`DebugSql`].
+	pub fn to_debug_sql(&self) -> String {
+		DebugSql::new().filter(self)
+	}
+}
+
+impl WhereFilter<'_> {
+	/// Renders the where clause with the params inlined, see [`DebugSql`].
+	pub fn to_debug_sql(&self) -> String {
+		DebugSql::new().where_filter(self)
+	}
+}
+
+fn params<'a>(
+	params: &'a Params,
+) -> impl Iterator<Item = (&'a str, &'a (dyn ToSql + Sync))> {
+	params.iter().map(|p| (p.name.as_str(), p.data.as_ref()))
+}
+
+/// Converts a value into a sql literal, the type is the first one the value
+/// accepts.
+fn literal(value: &(dyn ToSql + Sync)) -> Result<String, BoxError> {
+	let value = RawValue::encode(value)?;
+
+	let mut s = String::new();
+	match &value.raw {
+		Some(raw) => write_literal(&mut s, &value.ty, raw)?,
+		None => s.push_str("NULL"),
+	}
+
+	Ok(s)
+}
+
+fn write_literal(
+	s: &mut String,
+	ty: &Type,
+	raw: &[u8],
+) -> Result<(), BoxError> {
+	if let Kind::Array(member) = ty.kind() {
+		s.push_str("ARRAY[");
+		let mut values = pg::array_from_sql(raw)?.values();
+		let mut first = true;
+		while let Some(value) = values.next()? {
+			if !first {
+				s.push_str(", ");
+			}
+			first = false;
+
+			match value {
+				Some(raw) => write_literal(s, member, raw)?,
+				None => s.push_str("NULL"),
+			}
+		}
+		write!(s, "]::{}[]", member.name())?;
+
+		return Ok(());
+	}
+
+	match *ty {
+		Type::BOOL => match bool::from_sql(ty, raw)? {
+			true => s.push_str("TRUE"),
+			false => s.push_str("FALSE"),
+		},
+		Type::CHAR => write!(s, "{}", i8::from_sql(ty, raw)?)?,
+		Type::INT2 => write!(s, "{}", i16::from_sql(ty, raw)?)?,
+		Type::INT4 => write!(s, "{}", i32::from_sql(ty, raw)?)?,
+		Type::INT8 => write!(s, "{}", i64::from_sql(ty, raw)?)?,
+		Type::OID => write!(s, "{}", u32::from_sql(ty, raw)?)?,
+		Type::FLOAT4 => write_float(s, f32::from_sql(ty, raw)?.into(), ty),
+		Type::FLOAT8 => write_float(s, f64::from_sql(ty, raw)?, ty),
+		Type::BYTEA => {
+			s.push_str("'\\x");
+			for b in raw {
+				write!(s, "{b:02x}")?;
+			}
+			s.push_str("'::bytea");
+		}
+		Type::TIMESTAMP | Type::TIMESTAMPTZ => {
+			let micros = pg::timestamp_from_sql(raw)?;
+			let base = NaiveDate::from_ymd_opt(2000, 1, 1)
+				.and_then(|d| d.and_hms_opt(0, 0, 0))
+				.unwrap();
+			let text = match micros {
+				i64::MAX => "infinity".to_string(),
+				i64::MIN => "-infinity".to_string(),
+				micros => timestamp(base, micros)?,
+			};
+			let zone = if *ty == Type::TIMESTAMPTZ { "+00" } else { "" };
+			write!(s, "'{text}{zone}'::{}", ty.name())?;
+		}
+		Type::DATE => {
+			let days = pg::date_from_sql(raw)?;
+			let base = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+			let text = match days {
+				i32::MAX => "infinity".to_string(),
+				i32::MIN => "-infinity".to_string(),
+				days => base
+					.checked_add_signed(Duration::days(days.into()))
+					.ok_or("date out of range")?
+					.to_string(),
+			};
+			write!(s, "'{text}'::date")?;
+		}
+		// jsonb is prefixed with a version byte
+		Type::JSONB => {
+			let text = std::str::from_utf8(raw.get(1..).unwrap_or_default())?;
+			write_text(s, text);
+			s.push_str("::jsonb");
+		}
+		Type::JSON => {
+			write_text(s, std::str::from_utf8(raw)?);
+			s.push_str("::json");
+		}
+		Type::TEXT
+		| Type::VARCHAR
+		| Type::BPCHAR
+		| Type::NAME
+		| Type::UNKNOWN => write_text(s, std::str::from_utf8(raw)?),
+		// the binary format of other types is not text, the param stays as
+		// it is
+		_ => return Err(format!("unsupported type {}", ty).into()),
+	}
+
+	Ok(())
+}
+
+fn timestamp(base: NaiveDateTime, micros: i64) -> Result<String, BoxError> {
+	let date = base
+		.checked_add_signed(Duration::microseconds(micros))
+		.ok_or("timestamp out of range")?;
+
+	Ok(date.format("%Y-%m-%d %H:%M:%S%.6f").to_string())
+}
+
+fn write_float(s: &mut String, value: f64, ty: &Type) {
+	if value.is_finite() {
+		write!(s, "{value}").unwrap();
+	} else {
+		let text = match value {
+			f64::INFINITY => "Infinity",
+			f64::NEG_INFINITY => "-Infinity",
+			_ => "NaN",
+		};
+		write!(s, "'{text}'::{}", ty.name()).unwrap();
+	}
+}
+
+/// Writes a quoted string literal, quotes are doubled.
+fn write_text(s: &mut String, text: &str) {
+	s.push('\'');
+	s.push_str(&text.replace('\'', "''"));
+	s.push('\'');
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use crate::filter::Comparison;
+	use crate::row::RowBuilder;
+	use crate::{filter, whr, FromRow};
+
+	#[derive(FromRow)]
+	#[allow(dead_code)]
+	struct User {
+		id: i32,
+		name: String,
+	}
+
+	#[test]
+	fn test_literals() {
+		let ids = vec![1i64, 2];
+		let names: Vec<Option<String>> = vec![Some("a".into()), None];
+		let bytes = vec![0u8, 255];
+		let value: Option<i32> = None;
+		let float = 1.5f64;
+		let nan = f64::NAN;
+		let active = true;
+
+		let filter = Filter::new()
+			.and_in("id", &ids)
+			.and_eq("names", &names)
+			.and_eq("bytes", &bytes)
+			.and_eq("value", &value)
+			.and_lt("float", &float)
+			.and_ne("nan", &nan)
+			.and_eq("active", &active)
+			.and_sql("created", Comparison::Lt, "'$1'");
+
+		assert_eq!(
+			filter.to_debug_sql(),
+			r#" WHERE "id" = ANY(ARRAY[1, 2]::int8[]) AND "names" = ARRAY['a', NULL]::text[] AND "bytes" = '\x00ff'::bytea AND "value" IS NULL AND "float" < 1.5 AND "nan" != 'NaN'::float8 AND "active" = TRUE AND "created" < '$1'"#
+		);
+	}
+
+	#[test]
+	fn test_unsupported_literals() {
+		let mut s = String::new();
+		write_literal(&mut s, &Type::VARCHAR, b"it's").unwrap();
+		assert_eq!(s, "'it''s'");
+
+		// a uuid is binary and would not be valid text
+		let mut s = String::new();
+		assert!(write_literal(&mut s, &Type::UUID, &[0xff; 16]).is_err());
+		assert!(write_literal(&mut s, &Type::NUMERIC, &[0, 1, 0, 0]).is_err());
+	}
+
+	#[test]
+	fn test_statements() {
+		let id = 1;
+		let name = "Anna";
+		let token = "secret";
+		let debug =
+			DebugSql::new().redact_with(|column| column.ends_with("token"));
+
+		assert_eq!(
+			debug.select::<User>("users", &filter!("id" = &id LIMIT &id)),
+			r#"SELECT "id", "name" FROM "users" WHERE "id" = 1 LIMIT 1"#
+		);
+
+		let mut row = RowBuilder::new();
+		row.push("name", &name).push("token", &token);
+		assert_eq!(
+			debug.update("users", &row, &whr!("id" = &id)),
+			r#"UPDATE "users" SET "name" = 'Anna', "token" = '<redacted>' WHERE "id" = 1"#
+		);
+
+		assert_eq!(
+			debug.delete("users", &whr!("token" = &token)),
+			r#"DELETE FROM "users" WHERE "token" = '<redacted>'"#
+		);
+	}
+}
@@ -0,0 +1,138 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use crate::Ident;
+
+/// The tables of a select with joins, every table has an alias which is used
+/// to qualify the columns.
+///
+/// The columns of the filter need to be qualified as well, for example with
+/// `"u"."name"` in the [`filter!`](crate::filter!) macro or with
+/// [`Ident::qualified`].
+///
+/// ## Example
+/// ```
+/// use fire_postgres::filter::Join;
+/// use fire_postgres::Ident;
+///
+/// let join = Join::new("users", "u")
+/// 	.inner("memberships", "m", "user_id", Ident::qualified("u", "id"))
+/// 	.left("teams", "t", "id", Ident::qualified("m", "team_id"));
+///
+/// assert_eq!(
+/// 	join.to_string(),
+/// 	"\"users\" AS \"u\" \
+/// 	INNER JOIN \"memberships\" AS \"m\" ON \"m\".\"user_id\" = \"u\".\"id\" \
+/// 	LEFT JOIN \"teams\" AS \"t\" ON \"t\".\"id\" = \"m\".\"team_id\""
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Join {
+	pub table: Ident,
+	pub alias: Cow<'static, str>,
+	pub joins: Vec<JoinTable>,
+}
+
+/// A joined table `JOIN "table" AS "alias" ON "alias"."key" = on`
+#[derive(Debug, Clone)]
+pub struct JoinTable {
+	pub kind: JoinKind,
+	pub table: Ident,
+	pub alias: Cow<'static, str>,
+	/// A column of the joined table
+	pub key: Ident,
+	/// The qualified column the key needs to be equal to
+	pub on: Ident,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JoinKind {
+	Inner,
+	/// The columns of the joined table are null if no row matches
+	Left,
+}
+
+impl Join {
+	pub fn new(
+		table: impl Into<Ident>,
+		alias: impl Into<Cow<'static, str>>,
+	) -> Self {
+		Self {
+			table: table.into(),
+			alias: alias.into(),
+			joins: vec![],
+		}
+	}
+
+	/// Adds `INNER JOIN "table" AS "alias" ON "alias"."key" = on`.
+	pub fn inner(
+		self,
+		table: impl Into<Ident>,
+		alias: impl Into<Cow<'static, str>>,
+		key: impl Into<Ident>,
+		on: impl Into<Ident>,
+	) -> Self {
+		self.join(JoinKind::Inner, table.into(), alias.into(), key, on)
+	}
+
+	/// Adds `LEFT JOIN "table" AS "alias" ON "alias"."key" = on`.
+	pub fn left(
+		self,
+		table: impl Into<Ident>,
+		alias: impl Into<Cow<'static, str>>,
+		key: impl Into<Ident>,
+		on: impl Into<Ident>,
+	) -> Self {
+		self.join(JoinKind::Left, table.into(), alias.into(), key, on)
+	}
+
+	fn join(
+		mut self,
+		kind: JoinKind,
+		table: Ident,
+		alias: Cow<'static, str>,
+		key: impl Into<Ident>,
+		on: impl Into<Ident>,
+	) -> Self {
+		self.joins.push(JoinTable {
+			kind,
+			table,
+			key: key.into().with_table(alias.clone()),
+			alias,
+			on: on.into(),
+		});
+		self
+	}
+
+	/// Returns the aliases of all tables in the order they are joined.
+	pub(crate) fn aliases(&self) -> impl Iterator<Item = &str> {
+		let joins = self.joins.iter().map(|j| j.alias.as_ref());
+		[self.alias.as_ref()].into_iter().chain(joins)
+	}
+}
+
+/// Writes the tables after `FROM`.
+impl fmt::Display for Join {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{} AS {}", self.table, Ident::from(self.alias.clone()))?;
+
+		for join in &self.joins {
+			let kind = match join.kind {
+				JoinKind::Inner => "INNER JOIN",
+				JoinKind::Left => "LEFT JOIN",
+			};
+
+			write!(
+				f,
+				" {} {} AS {} ON {} = {}",
+				kind,
+				join.table,
+				Ident::from(join.alias.clone()),
+				join.key,
+				join.on
+			)?;
+		}
+
+		Ok(())
+	}
+}
@@ -0,0 +1,215 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use super::Operator;
+
+/// A path into a json or jsonb column.
+///
+/// ## Example
+/// ```
+/// use fire_postgres::filter::JsonPath;
+///
+/// let path = JsonPath::new().get("plan").get(0).get_text("name");
+/// assert_eq!(path.to_string(), "->'plan'->0->>'name'");
+///
+/// let path = JsonPath::new().path_text(["plan", "it's"]);
+/// assert_eq!(path.to_string(), "#>>ARRAY['plan','it''s']");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct JsonPath {
+	inner: Vec<JsonPathPart>,
+}
+
+#[derive(Debug, Clone)]
+pub enum JsonPathPart {
+	/// `->` returns json
+	Get(JsonKey),
+	/// `->>` returns text
+	GetText(JsonKey),
+	/// `#>` returns json
+	Path(Vec<Cow<'static, str>>),
+	/// `#>>` returns text
+	PathText(Vec<Cow<'static, str>>),
+}
+
+#[derive(Debug, Clone)]
+pub enum JsonKey {
+	Field(Cow<'static, str>),
+	Index(i32),
+}
+
+impl JsonPath {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Adds `->key`
+	pub fn get(mut self, key: impl Into<JsonKey>) -> Self {
+		self.inner.push(JsonPathPart::Get(key.into()));
+		self
+	}
+
+	/// Adds `->>key`, the result is text
+	pub fn get_text(mut self, key: impl Into<JsonKey>) -> Self {
+		self.inner.push(JsonPathPart::GetText(key.into()));
+		self
+	}
+
+	/// Adds `#>path`
+	pub fn path<I>(mut self, keys: I) -> Self
+	where
+		I: IntoIterator,
+		I::Item: Into<Cow<'static, str>>,
+	{
+		let keys = keys.into_iter().map(Into::into).collect();
+		self.inner.push(JsonPathPart::Path(keys));
+		self
+	}
+
+	/// Adds `#>>path`, the result is text
+	pub fn path_text<I>(mut self, keys: I) -> Self
+	where
+		I: IntoIterator,
+		I::Item: Into<Cow<'static, str>>,
+	{
+		let keys = keys.into_iter().map(Into::into).collect();
+		self.inner.push(JsonPathPart::PathText(keys));
+		self
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.inner.is_empty()
+	}
+
+	pub fn parts(&self) -> &[JsonPathPart] {
+		&self.inner
+	}
+}
+
+impl fmt::Display for JsonPath {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for part in &self.inner {
+			match part {
+				JsonPathPart::Get(key) => write!(f, "->{key}")?,
+				JsonPathPart::GetText(key) => write!(f, "->>{key}")?,
+				JsonPathPart::Path(keys) | JsonPathPart::PathText(keys) => {
+					if matches!(part, JsonPathPart::Path(_)) {
+						f.write_str("#>ARRAY[")?;
+					} else {
+						f.write_str("#>>ARRAY[")?;
+					}
+
+					for (i, key) in keys.iter().enumerate() {
+						if i != 0 {
+							f.write_str(",")?;
+						}
+
+						write_literal(f, key)?;
+					}
+
+					f.write_str("]")?;
+				}
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl fmt::Display for JsonKey {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Field(field) => write_literal(f, field),
+			Self::Index(idx) => write!(f, "{idx}"),
+		}
+	}
+}
+
+impl From<&'static str> for JsonKey {
+	fn from(s: &'static str) -> Self {
+		Self::Field(s.into())
+	}
+}
+
+impl From<String> for JsonKey {
+	fn from(s: String) -> Self {
+		Self::Field(s.into())
+	}
+}
+
+impl From<Cow<'static, str>> for JsonKey {
+	fn from(s: Cow<'static, str>) -> Self {
+		Self::Field(s)
+	}
+}
+
+impl From<i32> for JsonKey {
+	fn from(idx: i32) -> Self {
+		Self::Index(idx)
+	}
+}
+
+/// An operator which compares a json path with a value.
+///
+/// Paths returning text support the text operators, paths returning json the
+/// jsonb operators.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonComparison {
+	/// `=`, or `IS NULL` if the value is a null param
+	Eq,
+	/// `!=`, or `IS NOT NULL` if the value is a null param
+	Ne,
+	Lt,
+	Lte,
+	Gt,
+	Gte,
+	Like,
+	ILike,
+	NotLike,
+	NotILike,
+	Regex,
+	IRegex,
+	NotRegex,
+	NotIRegex,
+	SimilarTo,
+	Similar,
+	WordSimilar,
+	/// `@>` json only
+	Contains,
+	/// `<@` json only
+	ContainedBy,
+	/// `?` json only
+	HasKey,
+}
+
+impl From<JsonComparison> for Operator {
+	fn from(c: JsonComparison) -> Self {
+		match c {
+			JsonComparison::Eq => Self::Eq,
+			JsonComparison::Ne => Self::Ne,
+			JsonComparison::Lt => Self::Lt,
+			JsonComparison::Lte => Self::Lte,
+			JsonComparison::Gt => Self::Gt,
+			JsonComparison::Gte => Self::Gte,
+			JsonComparison::Like => Self::Like,
+			JsonComparison::ILike => Self::ILike,
+			JsonComparison::NotLike => Self::NotLike,
+			JsonComparison::NotILike => Self::NotILike,
+			JsonComparison::Regex => Self::Regex,
+			JsonComparison::IRegex => Self::IRegex,
+			JsonComparison::NotRegex => Self::NotRegex,
+			JsonComparison::NotIRegex => Self::NotIRegex,
+			JsonComparison::SimilarTo => Self::SimilarTo,
+			JsonComparison::Similar => Self::Similar,
+			JsonComparison::WordSimilar => Self::WordSimilar,
+			JsonComparison::Contains => Self::Contains,
+			JsonComparison::ContainedBy => Self::ContainedBy,
+			JsonComparison::HasKey => Self::HasKey,
+		}
+	}
+}
+
+/// Writes a string literal, quotes get escaped.
+fn write_literal(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+	write!(f, "'{}'", s.replace('\'', "''"))
+}
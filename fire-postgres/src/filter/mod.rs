@@ -5,10 +5,64 @@ use tokio_postgres::types::ToSql;
 use types::time::{Date, DateTime, Timeout};
 use types::uid::UniqueId;
 
+use crate::Ident;
+
+mod aggregate;
+mod builder;
+mod column;
+mod debug;
+mod join;
+mod json;
+mod order;
+mod page;
+mod query;
+mod search;
+mod sub_query;
 mod whr;
 
+pub use aggregate::{Aggregate, AggregateKind, Aggregates};
+#[doc(hidden)]
+pub use column::list_column;
+pub use column::{Col, IntoColumn, IntoListColumn, ListFor, ParamFor};
+pub use debug::DebugSql;
+pub use join::{Join, JoinKind, JoinTable};
+pub use json::{JsonComparison, JsonKey, JsonPath, JsonPathPart};
+pub use order::{
+	Direction, Nulls, OrderBy, OrderByPart, OrderExpr, ParseDirectionError,
+};
+pub(crate) use page::keyset_limit;
+pub use page::{Cursor, CursorError, Keyset, KeysetColumn, OffsetPage, Page};
+pub use query::{FilterParser, QueryError};
+pub use search::{TextSearch, TsQuery, TsVector};
+pub use sub_query::SubQuery;
+
+use order::fmt_order_by;
+
 pub type SqlStr = Cow<'static, str>;
 
+/// A filter can be created with the [`filter!`](crate::filter!) macro or with
+/// the builder methods if the conditions are only known at runtime.
+///
+/// A [`WhereFilter`] can be added to an existing filter with
+/// [`Filter::and`] or [`Filter::or`], the params get renumbered.
+///
+/// ## Example
+/// ```
+/// use fire_postgres::filter::Filter;
+///
+/// let status = "active";
+/// let name: Option<String> = None;
+/// let filter = Filter::new()
+/// 	.and_eq("status", &status)
+/// 	.and_like_opt("name", name.as_ref())
+/// 	.order_desc("created")
+/// 	.limit(20);
+///
+/// assert_eq!(
+/// 	filter.to_string(),
+/// 	r#" WHERE "status" = $1 ORDER BY "created" DESC LIMIT 20"#
+/// );
+/// ```
 #[derive(Debug)]
 #[non_exhaustive]
 pub struct Filter<'a> {
@@ -33,10 +87,10 @@ impl<'a> Filter<'a> {
 	pub(crate) fn to_formatter(&'a self) -> FilterFormatter<'a> {
 		FilterFormatter {
 			whr: &self.whr,
+			aggregates: None,
 			order_by: &self.order_by,
 			limit: &self.limit,
 			offset: &self.offset,
-			params: &self.params,
 		}
 	}
 }
@@ -51,26 +105,34 @@ impl fmt::Display for Filter<'_> {
 #[non_exhaustive]
 pub(crate) struct FilterFormatter<'a> {
 	pub whr: &'a Where,
+	/// Writes `GROUP BY` and `HAVING` after the where clause
+	pub aggregates: Option<&'a Aggregates<'a>>,
 	pub order_by: &'a OrderBy,
 	pub limit: &'a Limit,
 	pub offset: &'a Offset,
-	pub params: &'a Params<'a>,
 }
 
 impl fmt::Display for FilterFormatter<'_> {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-		self.whr.fmt(f)?;
+		// the params are in the same order as they appear in the sql
+		let mut param_num = 0;
 
-		self.order_by.fmt(f)?;
+		if !self.whr.is_empty() {
+			f.write_str(" WHERE ")?;
+			fmt_where(self.whr, f, &mut param_num)?;
+		}
 
-		let offset_has_param = matches!(self.offset, Offset::Param);
-		let param_count =
-			self.params.len() - if offset_has_param { 1 } else { 0 };
+		if let Some(aggregates) = self.aggregates {
+			aggregates.fmt_group_by(f, &mut param_num)?;
+		}
+
+		fmt_order_by(self.order_by, f, &mut param_num)?;
 
 		match &self.limit {
 			Limit::Fixed(value) => write!(f, " LIMIT {}", value)?,
 			Limit::Param => {
-				write!(f, " LIMIT ${}", param_count)?;
+				param_num += 1;
+				write!(f, " LIMIT ${}", param_num)?;
 			}
 			Limit::All => {}
 		}
@@ -79,7 +141,8 @@ impl fmt::Display for FilterFormatter<'_> {
 			Offset::Zero => {}
 			Offset::Fixed(value) => write!(f, " OFFSET {}", value)?,
 			Offset::Param => {
-				write!(f, " OFFSET ${}", self.params.len())?;
+				param_num += 1;
+				write!(f, " OFFSET ${}", param_num)?;
 			}
 		}
 
@@ -109,6 +172,12 @@ impl fmt::Display for WhereFilter<'_> {
 	}
 }
 
+impl<'a> From<WhereFilter<'a>> for Filter<'a> {
+	fn from(whr: WhereFilter<'a>) -> Self {
+		Self::from_where(whr)
+	}
+}
+
 #[derive(Debug)]
 pub struct Where {
 	inner: Vec<WherePart>,
@@ -119,12 +188,33 @@ pub enum WherePart {
 	Operation(WhereOperation),
 	And,
 	Or,
+	/// A clause wrapped in parentheses
+	Nested(Where),
+	/// A negated clause `NOT (...)`
+	Not(Where),
+	/// A raw sql fragment which is not escaped, it is always wrapped in
+	/// parentheses.
+	///
+	/// The placeholders `$1` to `$params` refer to the params of this
+	/// fragment and get renumbered when the filter is formatted.
+	Raw {
+		sql: SqlStr,
+		params: usize,
+	},
+	/// `EXISTS (SELECT 1 FROM ...)`
+	Exists(SubQuery),
+	/// The rows after a [`Cursor`], see [`Filter::after`]
+	Keyset(Keyset),
 }
 
 #[derive(Debug)]
 pub struct WhereOperation {
 	pub kind: Operator,
-	pub column: Cow<'static, str>,
+	pub column: Ident,
+	/// A path into a json column
+	pub path: Option<JsonPath>,
+	/// The right hand side, most of the time a param
+	pub value: WhereIdent,
 }
 
 #[derive(Debug)]
@@ -136,7 +226,50 @@ pub enum Operator {
 	Gt,
 	Gte,
 	Like,
-	In { length: usize },
+	ILike,
+	NotLike,
+	NotILike,
+	/// Posix regex `~`
+	Regex,
+	/// Case insensitive posix regex `~*`
+	IRegex,
+	NotRegex,
+	NotIRegex,
+	SimilarTo,
+	/// One param per element, the macros and the builder use [`Operator::Any`]
+	/// with a single array param instead, so the sql does not depend on the
+	/// number of elements.
+	In {
+		length: usize,
+	},
+	/// `column = ANY($1)` the param needs to be an array
+	Any,
+	/// `column != ALL($1)` the param needs to be an array
+	NeAll,
+	/// Array contains `@>`
+	Contains,
+	/// Array is contained by `<@`
+	ContainedBy,
+	/// Arrays overlap `&&`
+	Overlaps,
+	/// Json key exists `?`
+	HasKey,
+	/// Full text search `column @@ websearch_to_tsquery($1)`, the column
+	/// needs to be a tsvector
+	TextSearch(TextSearch),
+	/// Trigram similarity `%`, uses the `pg_trgm.similarity_threshold`
+	Similar,
+	/// Trigram word similarity `column %> $1`, uses the
+	/// `pg_trgm.word_similarity_threshold`
+	WordSimilar,
+	/// `similarity(column, $1) > threshold`
+	Similarity {
+		threshold: f64,
+	},
+	/// `word_similarity($1, column) > threshold`
+	WordSimilarity {
+		threshold: f64,
+	},
 
 	// rhs will be ignored
 	IsNull,
@@ -144,10 +277,82 @@ pub enum Operator {
 	IsNotNull,
 }
 
+/// An operator with exactly one value on the right hand side.
+///
+/// Used where the right hand side is another column, sql or an aggregate, so
+/// operators which need a list of params, functions or no value at all are
+/// not possible.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+	/// `=`, or `IS NULL` if the value is a null param
+	Eq,
+	/// `!=`, or `IS NOT NULL` if the value is a null param
+	Ne,
+	Lt,
+	Lte,
+	Gt,
+	Gte,
+	Like,
+	ILike,
+	NotLike,
+	NotILike,
+	Regex,
+	IRegex,
+	NotRegex,
+	NotIRegex,
+	SimilarTo,
+	Contains,
+	ContainedBy,
+	Overlaps,
+	HasKey,
+	Similar,
+	WordSimilar,
+}
+
+impl Comparison {
+	pub fn as_str(&self) -> &'static str {
+		Operator::from(*self).as_str()
+	}
+}
+
+impl From<Comparison> for Operator {
+	fn from(c: Comparison) -> Self {
+		match c {
+			Comparison::Eq => Self::Eq,
+			Comparison::Ne => Self::Ne,
+			Comparison::Lt => Self::Lt,
+			Comparison::Lte => Self::Lte,
+			Comparison::Gt => Self::Gt,
+			Comparison::Gte => Self::Gte,
+			Comparison::Like => Self::Like,
+			Comparison::ILike => Self::ILike,
+			Comparison::NotLike => Self::NotLike,
+			Comparison::NotILike => Self::NotILike,
+			Comparison::Regex => Self::Regex,
+			Comparison::IRegex => Self::IRegex,
+			Comparison::NotRegex => Self::NotRegex,
+			Comparison::NotIRegex => Self::NotIRegex,
+			Comparison::SimilarTo => Self::SimilarTo,
+			Comparison::Contains => Self::Contains,
+			Comparison::ContainedBy => Self::ContainedBy,
+			Comparison::Overlaps => Self::Overlaps,
+			Comparison::HasKey => Self::HasKey,
+			Comparison::Similar => Self::Similar,
+			Comparison::WordSimilar => Self::WordSimilar,
+		}
+	}
+}
+
+/// The right hand side of an operation
 #[derive(Debug)]
 pub enum WhereIdent {
 	Param,
-	Name(Cow<'static, str>),
+	/// Another column
+	Name(Ident),
+	/// A sql expression like `now()` or `CURRENT_DATE`, it is not escaped
+	Sql(Cow<'static, str>),
+	/// A sub query, with [`Operator::Any`] this is `column IN (SELECT ...)`
+	SubQuery(Box<SubQuery>),
 }
 
 impl Where {
@@ -159,19 +364,47 @@ impl Where {
 		self.inner.push(part.into());
 	}
 
-	fn is_empty(&self) -> bool {
+	pub(crate) fn is_empty(&self) -> bool {
 		self.inner.is_empty()
 	}
 
+	#[cfg(feature = "testing")]
+	pub(crate) fn parts(&self) -> &[WherePart] {
+		&self.inner
+	}
+
+	/// Returns true if the clause contains an `OR` which is not in
+	/// parentheses, raw fragments are always written in parentheses.
+	pub(crate) fn has_or(&self) -> bool {
+		self.inner.iter().any(|p| matches!(p, WherePart::Or))
+	}
+
+	/// Returns how many params the where clause needs.
+	pub fn param_count(&self) -> usize {
+		self.inner
+			.iter()
+			.map(|part| match part {
+				WherePart::Operation(op) => op.param_count(),
+				WherePart::And | WherePart::Or => 0,
+				WherePart::Nested(whr) | WherePart::Not(whr) => {
+					whr.param_count()
+				}
+				WherePart::Raw { params, .. } => *params,
+				WherePart::Exists(query) => query.param_count(),
+				WherePart::Keyset(keyset) => keyset.param_count(),
+			})
+			.sum()
+	}
+
 	pub(crate) fn to_formatter<'a>(&'a self) -> WhereFormatter<'a> {
 		WhereFormatter {
-			whr: &self,
+			whr: self,
 			param_start: 0,
 		}
 	}
 }
 
-impl<'a> fmt::Display for Where {
+impl fmt::Display for Where {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 		self.to_formatter().fmt(f)
 	}
@@ -192,128 +425,324 @@ impl<'a> fmt::Display for WhereFormatter<'a> {
 		f.write_str(" WHERE ")?;
 
 		let mut param_num = self.param_start;
-
-		for part in &self.whr.inner {
-			match part {
-				WherePart::And => f.write_str(" AND ")?,
-				WherePart::Or => f.write_str(" OR ")?,
-				WherePart::Operation(op) => match &op.kind {
-					Operator::IsNull | Operator::IsNotNull => {
-						write!(f, "\"{}\" {}", op.column, op.kind.as_str())?;
-					}
-					// handle in special if the length is zero
-					// in this case we wan't the query to always return no results
-					Operator::In { length } if *length == 0 => {
-						write!(f, "1=0")?;
-					}
-					Operator::In { length } => {
-						write!(f, "\"{}\" IN (", op.column)?;
-
-						for i in 0..*length {
-							if i != 0 {
-								f.write_str(", ")?;
-							}
-
-							param_num += 1;
-							write!(f, "${}", param_num)?;
-						}
-
-						f.write_str(")")?;
-					}
-					o => {
-						param_num += 1;
-
-						write!(
-							f,
-							"\"{}\" {} ${}",
-							op.column,
-							o.as_str(),
-							param_num
-						)?;
-					}
-				},
+		fmt_where(self.whr, f, &mut param_num)
+	}
+}
+
+/// Writes the where clause without the `WHERE` keyword, nested clauses are
+/// wrapped in parentheses.
+fn fmt_where(
+	whr: &Where,
+	f: &mut fmt::Formatter<'_>,
+	param_num: &mut usize,
+) -> fmt::Result {
+	for part in &whr.inner {
+		match part {
+			WherePart::And => f.write_str(" AND ")?,
+			WherePart::Or => f.write_str(" OR ")?,
+			WherePart::Nested(whr) | WherePart::Not(whr) => {
+				if matches!(part, WherePart::Not(_)) {
+					f.write_str("NOT ")?;
+				}
+
+				f.write_str("(")?;
+				if whr.is_empty() {
+					f.write_str("TRUE")?;
+				} else {
+					fmt_where(whr, f, param_num)?;
+				}
+				f.write_str(")")?;
+			}
+			WherePart::Operation(op) => fmt_operation(op, f, param_num)?,
+			// the fragment could contain an OR which would otherwise bind
+			// weaker than the surrounding AND
+			WherePart::Raw { sql, params } => {
+				f.write_str("(")?;
+				fmt_raw(sql, *params, f, param_num)?;
+				f.write_str(")")?;
+			}
+			WherePart::Exists(query) => {
+				write!(f, "EXISTS ({})", query.to_formatter(*param_num))?;
+				*param_num += query.param_count();
+			}
+			WherePart::Keyset(keyset) => {
+				write!(f, "{}", keyset.to_formatter(*param_num))?;
+				*param_num += keyset.param_count();
 			}
 		}
-
-		Ok(())
 	}
+
+	Ok(())
 }
 
-impl From<WhereOperation> for WherePart {
-	fn from(op: WhereOperation) -> Self {
-		Self::Operation(op)
+fn fmt_operation(
+	op: &WhereOperation,
+	f: &mut fmt::Formatter<'_>,
+	param_num: &mut usize,
+) -> fmt::Result {
+	// handle in special if the length is zero
+	// in this case we wan't the query to always return no results
+	if matches!(op.kind, Operator::In { length: 0 }) {
+		return f.write_str("1=0");
 	}
-}
 
-impl Operator {
-	fn as_str(&self) -> &str {
-		match self {
-			Operator::Eq => "=",
-			Operator::Ne => "!=",
-			Operator::Lt => "<",
-			Operator::Lte => "<=",
-			Operator::Gt => ">",
-			Operator::Gte => ">=",
-			Operator::Like => "LIKE",
-			Operator::In { .. } => "IN",
-			Operator::IsNull => "IS NULL",
-			Operator::IsNotNull => "IS NOT NULL",
+	// the column is an argument of the function
+	match &op.kind {
+		Operator::Similarity { threshold } => {
+			let rhs = next_rhs(&op.value, param_num);
+			f.write_str("similarity(")?;
+			fmt_column(op, f)?;
+			return write!(f, ", {}) > {}", rhs, threshold);
+		}
+		Operator::WordSimilarity { threshold } => {
+			let rhs = next_rhs(&op.value, param_num);
+			write!(f, "word_similarity({}, ", rhs)?;
+			fmt_column(op, f)?;
+			return write!(f, ") > {}", threshold);
+		}
+		_ => {}
+	}
+
+	fmt_column(op, f)?;
+
+	match &op.kind {
+		Operator::IsNull | Operator::IsNotNull => {
+			write!(f, " {}", op.kind.as_str())
+		}
+		Operator::In { length } => {
+			f.write_str(" IN (")?;
+
+			for i in 0..*length {
+				if i != 0 {
+					f.write_str(", ")?;
+				}
+
+				*param_num += 1;
+				write!(f, "${}", param_num)?;
+			}
+
+			f.write_str(")")
+		}
+		Operator::TextSearch(search) => {
+			let rhs = next_rhs(&op.value, param_num);
+			write!(f, " @@ {}", search.to_formatter(rhs))
+		}
+		o @ (Operator::Any | Operator::NeAll) => {
+			let rhs = next_rhs(&op.value, param_num);
+			// a sub query already has parentheses
+			if matches!(op.value, WhereIdent::SubQuery(_)) {
+				write!(f, " {}{}", o.as_str(), rhs)
+			} else {
+				write!(f, " {}({})", o.as_str(), rhs)
+			}
+		}
+		o => {
+			let rhs = next_rhs(&op.value, param_num);
+			write!(f, " {} {}", o.as_str(), rhs)
 		}
 	}
 }
 
-#[derive(Debug)]
-pub struct OrderBy {
-	inner: Vec<OrderByPart>,
+/// Writes the sql and renumbers the `$n` placeholders, so they start after
+/// `param_num`.
+fn fmt_raw(
+	sql: &str,
+	params: usize,
+	f: &mut fmt::Formatter<'_>,
+	param_num: &mut usize,
+) -> fmt::Result {
+	let mut last = 0;
+	for placeholder in placeholders(sql) {
+		f.write_str(&sql[last..placeholder.start])?;
+		write!(f, "${}", *param_num + placeholder.num)?;
+		last = placeholder.end;
+	}
+	f.write_str(&sql[last..])?;
+
+	*param_num += params;
+
+	Ok(())
 }
 
-#[derive(Debug)]
-pub enum OrderByPart {
-	Asc(Cow<'static, str>),
-	Desc(Cow<'static, str>),
+/// A `$n` placeholder in raw sql, `start..end` is the byte range of `$n`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Placeholder {
+	pub start: usize,
+	pub end: usize,
+	pub num: usize,
 }
 
-impl OrderBy {
-	pub fn new() -> Self {
-		Self { inner: vec![] }
-	}
+/// Returns all `$n` placeholders which are not inside a string literal, a
+/// quoted identifier or a dollar quoted string.
+///
+/// A number which does not fit into an usize is returned as `usize::MAX`.
+pub(crate) fn placeholders(sql: &str) -> Vec<Placeholder> {
+	let mut list = vec![];
+	let mut i = 0;
 
-	pub fn push_asc(&mut self, column: impl Into<Cow<'static, str>>) {
-		self.inner.push(OrderByPart::Asc(column.into()));
+	while let Some(c) = sql[i..].chars().next() {
+		let rest = &sql[i + 1..];
+		match c {
+			// an escaped quote just ends and starts the quote again
+			'\'' | '"' => {
+				i = rest.find(c).map_or(sql.len(), |end| i + end + 2);
+			}
+			'$' => {
+				let len = rest
+					.find(|c: char| !c.is_ascii_digit())
+					.unwrap_or(rest.len());
+				if len > 0 {
+					list.push(Placeholder {
+						start: i,
+						end: i + 1 + len,
+						num: rest[..len].parse().unwrap_or(usize::MAX),
+					});
+					i += 1 + len;
+					continue;
+				}
+
+				// a dollar quote `$tag$` ends with the same tag
+				let len = rest
+					.find(|c: char| !(c.is_alphanumeric() || c == '_'))
+					.unwrap_or(rest.len());
+				if !rest[len..].starts_with('$') {
+					i += 1;
+					continue;
+				}
+
+				let tag = &sql[i..i + len + 2];
+				let body = i + tag.len();
+				i = sql[body..]
+					.find(tag)
+					.map_or(sql.len(), |end| body + end + tag.len());
+			}
+			c => i += c.len_utf8(),
+		}
 	}
 
-	pub fn push_desc(&mut self, column: impl Into<Cow<'static, str>>) {
-		self.inner.push(OrderByPart::Desc(column.into()));
+	list
+}
+
+/// Returns the right hand side of an operation, only params increase the
+/// param number.
+fn next_rhs<'a>(ident: &'a WhereIdent, param_num: &mut usize) -> Rhs<'a> {
+	let start = *param_num;
+	match ident {
+		WhereIdent::Param => *param_num += 1,
+		WhereIdent::SubQuery(query) => *param_num += query.param_count(),
+		WhereIdent::Name(_) | WhereIdent::Sql(_) => {}
 	}
 
-	fn is_empty(&self) -> bool {
-		self.inner.is_empty()
+	Rhs {
+		ident,
+		param_start: start,
 	}
 }
 
-impl fmt::Display for OrderBy {
+struct Rhs<'a> {
+	ident: &'a WhereIdent,
+	/// indexed by zero
+	param_start: usize,
+}
+
+impl fmt::Display for Rhs<'_> {
 	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-		if self.is_empty() {
-			return Ok(());
+		match self.ident {
+			WhereIdent::Param => write!(f, "${}", self.param_start + 1),
+			WhereIdent::Name(name) => write!(f, "{}", name),
+			WhereIdent::Sql(sql) => f.write_str(sql),
+			WhereIdent::SubQuery(query) => {
+				write!(f, "({})", query.to_formatter(self.param_start))
+			}
 		}
+	}
+}
 
-		f.write_str(" ORDER BY ")?;
+fn fmt_column(op: &WhereOperation, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+	write!(f, "{}", op.column)?;
+	if let Some(path) = &op.path {
+		write!(f, "{path}")?;
+	}
 
-		for (i, part) in self.inner.iter().enumerate() {
-			if i != 0 {
-				f.write_str(", ")?;
-			}
+	Ok(())
+}
 
-			match part {
-				OrderByPart::Asc(column) => write!(f, "\"{}\" ASC", column)?,
-				OrderByPart::Desc(column) => write!(f, "\"{}\" DESC", column)?,
-			}
+impl WhereOperation {
+	/// Returns how many params the operation needs.
+	pub fn param_count(&self) -> usize {
+		match (&self.kind, &self.value) {
+			(Operator::IsNull | Operator::IsNotNull, _) => 0,
+			(Operator::In { length }, _) => *length,
+			(_, WhereIdent::Param) => 1,
+			(_, WhereIdent::SubQuery(query)) => query.param_count(),
+			(_, WhereIdent::Name(_) | WhereIdent::Sql(_)) => 0,
 		}
+	}
+}
 
-		Ok(())
+impl From<WhereOperation> for WherePart {
+	fn from(op: WhereOperation) -> Self {
+		Self::Operation(op)
+	}
+}
+
+impl Operator {
+	fn as_str(&self) -> &'static str {
+		match self {
+			Operator::Eq => "=",
+			Operator::Ne => "!=",
+			Operator::Lt => "<",
+			Operator::Lte => "<=",
+			Operator::Gt => ">",
+			Operator::Gte => ">=",
+			Operator::Like => "LIKE",
+			Operator::ILike => "ILIKE",
+			Operator::NotLike => "NOT LIKE",
+			Operator::NotILike => "NOT ILIKE",
+			Operator::Regex => "~",
+			Operator::IRegex => "~*",
+			Operator::NotRegex => "!~",
+			Operator::NotIRegex => "!~*",
+			Operator::SimilarTo => "SIMILAR TO",
+			Operator::In { .. } => "IN",
+			Operator::Any => "= ANY",
+			Operator::NeAll => "!= ALL",
+			Operator::Contains => "@>",
+			Operator::ContainedBy => "<@",
+			Operator::Overlaps => "&&",
+			Operator::HasKey => "?",
+			Operator::TextSearch(_) => "@@",
+			Operator::Similar => "%",
+			Operator::WordSimilar => "%>",
+			Operator::Similarity { .. } => "similarity",
+			Operator::WordSimilarity { .. } => "word_similarity",
+			Operator::IsNull => "IS NULL",
+			Operator::IsNotNull => "IS NOT NULL",
+		}
 	}
 }
 
+/// Escapes `%`, `_` and `\` so the value matches literally in a LIKE
+/// pattern.
+///
+/// ## Example
+/// ```
+/// use fire_postgres::filter::escape_like;
+///
+/// assert_eq!(escape_like(r"100%_a\"), r"100\%\_a\\");
+/// ```
+pub fn escape_like(value: &str) -> String {
+	let mut s = String::with_capacity(value.len());
+	for c in value.chars() {
+		if matches!(c, '%' | '_' | '\\') {
+			s.push('\\');
+		}
+		s.push(c);
+	}
+
+	s
+}
+
 #[derive(Debug)]
 pub enum Limit {
 	Fixed(usize),
@@ -370,6 +799,16 @@ impl<'a> Params<'a> {
 		self.inner.push(param);
 	}
 
+	/// Appends all params from other, used for nested clauses.
+	pub fn append(&mut self, other: Params<'a>) {
+		self.inner.extend(other.inner);
+	}
+
+	/// Inserts a param at the given position, shifting all params after it.
+	pub(crate) fn insert(&mut self, index: usize, param: Param<'a>) {
+		self.inner.insert(index, param);
+	}
+
 	pub fn len(&self) -> usize {
 		self.inner.len()
 	}
@@ -378,6 +817,10 @@ impl<'a> Params<'a> {
 		self.inner.is_empty()
 	}
 
+	pub fn iter(&self) -> impl ExactSizeIterator<Item = &Param<'a>> {
+		self.inner.iter()
+	}
+
 	pub fn iter_to_sql(
 		&self,
 	) -> impl ExactSizeIterator<Item = &(dyn ToSql + Sync)> {
@@ -388,35 +831,61 @@ impl<'a> Params<'a> {
 #[derive(Debug)]
 #[non_exhaustive]
 pub struct Param<'a> {
-	// todo is the name still needed?
-	pub name: &'static str,
+	/// The column the param belongs to, used to redact params in
+	/// [`DebugSql`].
+	pub name: Ident,
 	pub data: CowParamData<'a>,
 	is_null: bool,
 }
 
 impl<'a> Param<'a> {
-	pub fn new<T>(name: &'static str, data: &'a T) -> Self
+	/// A typed [`Col`] only accepts values which match its type.
+	pub fn new<T>(name: impl IntoColumn<T>, data: &'a T) -> Self
 	where
 		T: ParamData + ToSql + Send + Sync,
 	{
 		Self {
-			name,
+			name: name.into_column(),
 			is_null: data.is_null(),
 			data: CowParamData::Borrowed(data),
 		}
 	}
 
-	pub fn new_owned<T>(name: &'static str, data: T) -> Self
+	/// A typed [`Col`] only accepts lists whose elements match its type.
+	pub fn new_list<T>(name: impl IntoListColumn<T>, data: &'a T) -> Self
 	where
-		T: ParamData + ToSql + Send + Sync + 'static,
+		T: ParamData + ToSql + Send + Sync,
 	{
 		Self {
-			name,
+			name: name.into_list_column(),
+			is_null: data.is_null(),
+			data: CowParamData::Borrowed(data),
+		}
+	}
+
+	pub fn new_owned<T>(name: impl Into<Ident>, data: T) -> Self
+	where
+		T: ParamData + ToSql + Send + Sync + 'a,
+	{
+		Self {
+			name: name.into(),
 			is_null: data.is_null(),
 			data: CowParamData::Owned(Box::new(data)),
 		}
 	}
 
+	/// A param which is never treated as null, used for raw sql.
+	pub(crate) fn from_dyn(
+		name: impl Into<Ident>,
+		data: &'a (dyn ToSql + Send + Sync),
+	) -> Self {
+		Self {
+			name: name.into(),
+			is_null: false,
+			data: CowParamData::Borrowed(data),
+		}
+	}
+
 	pub fn is_null(&self) -> bool {
 		self.is_null
 	}
@@ -425,10 +894,11 @@ impl<'a> Param<'a> {
 #[derive(Debug)]
 pub enum CowParamData<'a> {
 	Borrowed(&'a (dyn ToSql + Send + Sync)),
-	Owned(Box<dyn ToSql + Send + Sync>),
+	Owned(Box<dyn ToSql + Send + Sync + 'a>),
 }
 
 impl<'a> CowParamData<'a> {
+	#[allow(clippy::should_implement_trait)]
 	pub fn as_ref(&self) -> &(dyn ToSql + Sync) {
 		match self {
 			CowParamData::Borrowed(data) => *data,
@@ -510,6 +980,21 @@ param_not_null!(
 	Timeout
 );
 
+// a json null is not an sql null
+#[cfg(feature = "json")]
+impl ParamData for serde_json::Value {
+	fn is_null(&self) -> bool {
+		false
+	}
+}
+
+#[cfg(feature = "json")]
+impl<T> ParamData for types::json::Json<T> {
+	fn is_null(&self) -> bool {
+		false
+	}
+}
+
 #[cfg(feature = "email")]
 impl ParamData for email_address::EmailAddress {
 	fn is_null(&self) -> bool {
@@ -0,0 +1,312 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use super::{SqlStr, TextSearch};
+use crate::Ident;
+
+#[derive(Debug)]
+pub struct OrderBy {
+	inner: Vec<OrderByPart>,
+}
+
+/// One expression of an `ORDER BY` clause.
+///
+/// This used to be an enum with the variants `Asc` and `Desc`, the deprecated
+/// functions with the same names construct the equivalent part.
+///
+/// ## Example
+/// ```
+/// use fire_postgres::filter::{Direction, Filter, OrderByPart};
+///
+/// // the column comes from user input and is checked against a list
+/// let sort = "name";
+/// let dir: Direction = "desc".parse().unwrap();
+/// let part = OrderByPart::allowed(sort, dir, &["name", "created"]).unwrap();
+///
+/// let filter = Filter::new()
+/// 	.order_by(part.nulls_last())
+/// 	.order_by(OrderByPart::sql("random()", Direction::Asc));
+/// assert_eq!(
+/// 	filter.to_string(),
+/// 	r#" ORDER BY "name" DESC NULLS LAST, random() ASC"#
+/// );
+///
+/// assert!(OrderByPart::allowed("password", dir, &["name"]).is_none());
+/// ```
+#[derive(Debug)]
+pub struct OrderByPart {
+	pub expr: OrderExpr,
+	pub direction: Direction,
+	/// If not set postgres puts nulls last with `ASC` and first with `DESC`
+	pub nulls: Option<Nulls>,
+}
+
+#[derive(Debug)]
+pub enum OrderExpr {
+	Column(Ident),
+	/// A sql expression like `lower("name")` or `random()`, it is not escaped
+	Sql(SqlStr),
+	/// Orders by `ts_rank(column, query)`.
+	///
+	/// Needs a param with the search text.
+	Rank {
+		column: Ident,
+		search: TextSearch,
+	},
+	/// Orders by the trigram distance `column <-> $1`.
+	///
+	/// Needs a param with the text to compare to.
+	Distance(Ident),
+}
+
+#[derive(
+	Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+	#[default]
+	Asc,
+	Desc,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Nulls {
+	First,
+	Last,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[error("Expected asc or desc")]
+pub struct ParseDirectionError;
+
+impl OrderBy {
+	pub fn new() -> Self {
+		Self { inner: vec![] }
+	}
+
+	/// If the part needs a param it needs to be pushed after all where params
+	/// and before the limit and offset params.
+	pub fn push(&mut self, part: OrderByPart) {
+		self.inner.push(part);
+	}
+
+	pub fn push_asc(&mut self, column: impl Into<Ident>) {
+		self.push(OrderByPart::asc(column));
+	}
+
+	pub fn push_desc(&mut self, column: impl Into<Ident>) {
+		self.push(OrderByPart::desc(column));
+	}
+
+	/// Orders by `ts_rank(column, query)`, the most relevant rows first.
+	///
+	/// The param with the search text needs to be pushed after all where
+	/// params and before the limit and offset params.
+	pub fn push_rank(&mut self, column: impl Into<Ident>, search: TextSearch) {
+		self.push(OrderByPart::new(
+			OrderExpr::Rank {
+				column: column.into(),
+				search,
+			},
+			Direction::Desc,
+		));
+	}
+
+	/// Orders by the trigram distance `column <-> $1`, the most similar rows
+	/// first.
+	///
+	/// The param with the text needs to be pushed after all where params and
+	/// before the limit and offset params.
+	pub fn push_distance(&mut self, column: impl Into<Ident>) {
+		self.push(OrderByPart::new(
+			OrderExpr::Distance(column.into()),
+			Direction::Asc,
+		));
+	}
+
+	pub(crate) fn is_empty(&self) -> bool {
+		self.inner.is_empty()
+	}
+
+	pub(crate) fn parts(&self) -> &[OrderByPart] {
+		&self.inner
+	}
+
+	/// Returns how many params the order by clause needs.
+	pub(crate) fn param_count(&self) -> usize {
+		self.inner
+			.iter()
+			.filter(|p| {
+				matches!(
+					p.expr,
+					OrderExpr::Rank { .. } | OrderExpr::Distance(_)
+				)
+			})
+			.count()
+	}
+}
+
+impl fmt::Display for OrderBy {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		fmt_order_by(self, f, &mut 0)
+	}
+}
+
+impl OrderByPart {
+	pub fn new(expr: OrderExpr, direction: Direction) -> Self {
+		Self {
+			expr,
+			direction,
+			nulls: None,
+		}
+	}
+
+	pub fn column(column: impl Into<Ident>, direction: Direction) -> Self {
+		Self::new(OrderExpr::Column(column.into()), direction)
+	}
+
+	pub fn asc(column: impl Into<Ident>) -> Self {
+		Self::column(column, Direction::Asc)
+	}
+
+	pub fn desc(column: impl Into<Ident>) -> Self {
+		Self::column(column, Direction::Desc)
+	}
+
+	/// Orders by a sql expression, which is not escaped.
+	pub fn sql(sql: impl Into<SqlStr>, direction: Direction) -> Self {
+		Self::new(OrderExpr::Sql(sql.into()), direction)
+	}
+
+	/// Orders by a column which comes from user input, returns `None` if the
+	/// column is not in the allowed list.
+	pub fn allowed(
+		column: &str,
+		direction: Direction,
+		allowed: &[&'static str],
+	) -> Option<Self> {
+		allowed
+			.iter()
+			.find(|c| **c == column)
+			.map(|c| Self::column(*c, direction))
+	}
+
+	pub fn nulls_first(mut self) -> Self {
+		self.nulls = Some(Nulls::First);
+		self
+	}
+
+	pub fn nulls_last(mut self) -> Self {
+		self.nulls = Some(Nulls::Last);
+		self
+	}
+
+	/// Returns true if nulls come before all other values.
+	pub fn nulls_are_first(&self) -> bool {
+		match self.nulls {
+			Some(nulls) => nulls == Nulls::First,
+			None => self.direction == Direction::Desc,
+		}
+	}
+
+	// `OrderByPart` used to be an enum, those functions keep the old
+	// constructors compiling, matching on the variants is no longer possible
+
+	#[deprecated(note = "use `OrderByPart::asc`")]
+	#[allow(non_snake_case)]
+	pub fn Asc(column: impl Into<Ident>) -> Self {
+		Self::asc(column)
+	}
+
+	#[deprecated(note = "use `OrderByPart::desc`")]
+	#[allow(non_snake_case)]
+	pub fn Desc(column: impl Into<Ident>) -> Self {
+		Self::desc(column)
+	}
+}
+
+impl Direction {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::Asc => "ASC",
+			Self::Desc => "DESC",
+		}
+	}
+
+	pub fn reverse(self) -> Self {
+		match self {
+			Self::Asc => Self::Desc,
+			Self::Desc => Self::Asc,
+		}
+	}
+}
+
+/// Parses `asc` or `desc` ignoring the case.
+impl FromStr for Direction {
+	type Err = ParseDirectionError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		if s.eq_ignore_ascii_case("asc") {
+			Ok(Self::Asc)
+		} else if s.eq_ignore_ascii_case("desc") {
+			Ok(Self::Desc)
+		} else {
+			Err(ParseDirectionError)
+		}
+	}
+}
+
+impl Nulls {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::First => "NULLS FIRST",
+			Self::Last => "NULLS LAST",
+		}
+	}
+}
+
+pub(super) fn fmt_order_by(
+	order_by: &OrderBy,
+	f: &mut fmt::Formatter<'_>,
+	param_num: &mut usize,
+) -> fmt::Result {
+	if order_by.is_empty() {
+		return Ok(());
+	}
+
+	f.write_str(" ORDER BY ")?;
+
+	for (i, part) in order_by.inner.iter().enumerate() {
+		if i != 0 {
+			f.write_str(", ")?;
+		}
+
+		match &part.expr {
+			OrderExpr::Column(column) => write!(f, "{}", column)?,
+			OrderExpr::Sql(sql) => f.write_str(sql)?,
+			OrderExpr::Rank { column, search } => {
+				*param_num += 1;
+				write!(
+					f,
+					"ts_rank({}, {})",
+					column,
+					search.to_formatter(format_args!("${}", *param_num))
+				)?;
+			}
+			OrderExpr::Distance(column) => {
+				*param_num += 1;
+				write!(f, "{} <-> ${}", column, param_num)?;
+			}
+		}
+
+		write!(f, " {}", part.direction.as_str())?;
+
+		if let Some(nulls) = &part.nulls {
+			write!(f, " {}", nulls.as_str())?;
+		}
+	}
+
+	Ok(())
+}
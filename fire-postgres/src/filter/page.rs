@@ -0,0 +1,451 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+use base64::engine::{general_purpose::URL_SAFE_NO_PAD, Engine};
+use base64::DecodeError;
+use postgres_types::Type;
+use serde::de::{Deserializer, Error as DeError};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+
+use super::{Direction, Filter, Limit, OrderBy, OrderExpr, ParamData};
+use crate::row::FromRowOwned;
+use crate::row::RawValue;
+use crate::{Ident, Row};
+
+/// A page of rows returned by keyset pagination.
+///
+/// Pass `next` to [`Filter::after`](super::Filter::after) to get the
+/// following page, it is `None` if this is the last page.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+	pub items: Vec<T>,
+	pub next: Option<Cursor>,
+}
+
+impl<T> Page<T> {
+	/// Creates a page from rows which were selected with the limit returned
+	/// by [`keyset_limit`], the extra row is only used to know if there is a
+	/// next page.
+	pub(crate) fn from_rows(
+		mut rows: Vec<Row>,
+		order_by: &OrderBy,
+		limit: Option<usize>,
+	) -> Result<Self, crate::Error>
+	where
+		T: FromRowOwned,
+	{
+		let next = match limit {
+			Some(limit) if rows.len() > limit => {
+				rows.truncate(limit);
+				rows.last()
+					.map(|row| Cursor::from_row(order_by, row))
+					.transpose()?
+			}
+			_ => None,
+		};
+
+		let items = rows
+			.into_iter()
+			.map(|row| {
+				T::from_row_owned(row).map_err(crate::Error::Deserialize)
+			})
+			.collect::<Result<_, _>>()?;
+
+		Ok(Self { items, next })
+	}
+}
+
+/// Checks that the filter can be used for keyset pagination and increases
+/// the limit by one, returns the page size.
+pub(crate) fn keyset_limit(
+	filter: &mut Filter,
+) -> Result<Option<usize>, crate::Error> {
+	Keyset::new(&filter.order_by)?;
+
+	match filter.limit {
+		Limit::Fixed(limit) => {
+			filter.limit = Limit::Fixed(limit + 1);
+			Ok(Some(limit))
+		}
+		Limit::All => Ok(None),
+		Limit::Param => Err(crate::Error::Unknown(
+			"keyset pagination needs a fixed limit".into(),
+		)),
+	}
+}
+
+/// A page of rows selected with an offset, together with the number of all
+/// rows which match the filter.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OffsetPage<T> {
+	pub items: Vec<T>,
+	pub total: u64,
+	/// The page starting at zero
+	pub page: usize,
+	pub per_page: usize,
+}
+
+impl<T> OffsetPage<T> {
+	/// Returns the number of pages.
+	pub fn pages(&self) -> u64 {
+		match self.per_page {
+			0 => 0,
+			per_page => self.total.div_ceil(per_page as u64),
+		}
+	}
+
+	/// Returns true if there is a page after this one.
+	pub fn has_next(&self) -> bool {
+		(self.page as u64) + 1 < self.pages()
+	}
+}
+
+/// The position after the last row of a page.
+///
+/// Contains the values of the order by columns of the last row, it is
+/// serialized as an opaque url safe base64 string.
+///
+/// ## Example
+/// ```
+/// use fire_postgres::filter::Cursor;
+///
+/// let cursor: Cursor = "AAAAFwAAAAQAAAAq".parse().unwrap();
+/// assert_eq!(cursor.len(), 1);
+/// assert_eq!(cursor.to_string(), "AAAAFwAAAAQAAAAq");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cursor {
+	values: Vec<RawValue>,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum CursorError {
+	#[error("The cursor is not valid base64 {0}")]
+	Decode(#[from] DecodeError),
+
+	#[error("The cursor is malformed")]
+	Malformed,
+
+	#[error("The cursor contains the unknown type {0}")]
+	UnknownType(u32),
+
+	#[error("The cursor has {found} values but {expected} were expected")]
+	Mismatch { expected: usize, found: usize },
+
+	#[error("Keyset pagination needs an order by which only contains columns")]
+	Unordered,
+}
+
+impl Cursor {
+	pub fn new(values: Vec<RawValue>) -> Self {
+		Self { values }
+	}
+
+	/// Reads the values of the order by columns from the row.
+	///
+	/// All order by columns need to be selected.
+	pub fn from_row(
+		order_by: &OrderBy,
+		row: &Row,
+	) -> Result<Self, crate::Error> {
+		let columns = keyset_columns(order_by)?;
+
+		columns
+			.iter()
+			.map(|col| row.try_get::<_, RawValue>(col.name.as_ref()))
+			.collect::<Result<_, _>>()
+			.map(Self::new)
+	}
+
+	pub fn values(&self) -> &[RawValue] {
+		&self.values
+	}
+
+	pub fn len(&self) -> usize {
+		self.values.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.values.is_empty()
+	}
+
+	/// Every value is stored as its type oid followed by the length and the
+	/// binary postgres representation, a null has the length -1.
+	fn to_bytes(&self) -> Vec<u8> {
+		let mut bytes = vec![];
+		for value in &self.values {
+			bytes.extend_from_slice(&value.ty.oid().to_be_bytes());
+
+			match &value.raw {
+				Some(raw) => {
+					bytes.extend_from_slice(&(raw.len() as i32).to_be_bytes());
+					bytes.extend_from_slice(raw);
+				}
+				None => bytes.extend_from_slice(&(-1i32).to_be_bytes()),
+			}
+		}
+
+		bytes
+	}
+
+	fn from_bytes(mut bytes: &[u8]) -> Result<Self, CursorError> {
+		fn take<'a>(
+			bytes: &mut &'a [u8],
+			len: usize,
+		) -> Result<&'a [u8], CursorError> {
+			if bytes.len() < len {
+				return Err(CursorError::Malformed);
+			}
+
+			let (a, b) = bytes.split_at(len);
+			*bytes = b;
+			Ok(a)
+		}
+
+		let mut values = vec![];
+		while !bytes.is_empty() {
+			let oid = take(&mut bytes, 4)?;
+			let oid = u32::from_be_bytes(oid.try_into().unwrap());
+			let ty =
+				Type::from_oid(oid).ok_or(CursorError::UnknownType(oid))?;
+
+			let len = take(&mut bytes, 4)?;
+			let len = i32::from_be_bytes(len.try_into().unwrap());
+			let raw = match usize::try_from(len) {
+				Ok(len) => Some(take(&mut bytes, len)?.to_vec()),
+				Err(_) if len == -1 => None,
+				Err(_) => return Err(CursorError::Malformed),
+			};
+
+			values.push(RawValue { ty, raw });
+		}
+
+		Ok(Self { values })
+	}
+}
+
+impl fmt::Display for Cursor {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&URL_SAFE_NO_PAD.encode(self.to_bytes()))
+	}
+}
+
+impl FromStr for Cursor {
+	type Err = CursorError;
+
+	fn from_str(s: &str) -> Result<Self, Self::Err> {
+		let bytes = URL_SAFE_NO_PAD.decode(s)?;
+		Self::from_bytes(&bytes)
+	}
+}
+
+impl Serialize for Cursor {
+	fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+	where
+		S: Serializer,
+	{
+		serializer.collect_str(self)
+	}
+}
+
+impl<'de> Deserialize<'de> for Cursor {
+	fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+	where
+		D: Deserializer<'de>,
+	{
+		let s: Cow<'_, str> = Deserialize::deserialize(deserializer)?;
+		s.parse().map_err(D::Error::custom)
+	}
+}
+
+impl ParamData for RawValue {
+	fn is_null(&self) -> bool {
+		self.is_null()
+	}
+}
+
+/// Matches all rows which come after the values of a cursor in the order of
+/// the columns.
+///
+/// If all columns have the same direction a row comparison
+/// `("created", "id") < ($1, $2)` is used, which can use an index on both
+/// columns. The columns should not be nullable, a null never matches.
+#[derive(Debug)]
+pub struct Keyset {
+	pub columns: Vec<KeysetColumn>,
+}
+
+#[derive(Debug, Clone)]
+pub struct KeysetColumn {
+	pub name: Ident,
+	pub desc: bool,
+}
+
+impl Keyset {
+	pub fn new(order_by: &OrderBy) -> Result<Self, CursorError> {
+		keyset_columns(order_by).map(|columns| Self { columns })
+	}
+
+	pub fn param_count(&self) -> usize {
+		self.columns.len()
+	}
+
+	pub(crate) fn to_formatter(
+		&self,
+		param_start: usize,
+	) -> KeysetFormatter<'_> {
+		KeysetFormatter {
+			keyset: self,
+			param_start,
+		}
+	}
+}
+
+fn keyset_columns(
+	order_by: &OrderBy,
+) -> Result<Vec<KeysetColumn>, CursorError> {
+	if order_by.is_empty() {
+		return Err(CursorError::Unordered);
+	}
+
+	order_by
+		.parts()
+		.iter()
+		.map(|part| match &part.expr {
+			// the comparison in the where clause does not know where nulls
+			// are ordered
+			_ if part.nulls.is_some() => Err(CursorError::Unordered),
+			OrderExpr::Column(name) => Ok(KeysetColumn {
+				name: name.clone(),
+				desc: part.direction == Direction::Desc,
+			}),
+			_ => Err(CursorError::Unordered),
+		})
+		.collect()
+}
+
+pub(crate) struct KeysetFormatter<'a> {
+	keyset: &'a Keyset,
+	/// indexed by zero
+	param_start: usize,
+}
+
+impl fmt::Display for KeysetFormatter<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let columns = &self.keyset.columns;
+		let param = |i: usize| self.param_start + i + 1;
+		let op = |col: &KeysetColumn| if col.desc { "<" } else { ">" };
+
+		if let [col] = columns.as_slice() {
+			return write!(f, "{} {} ${}", col.name, op(col), param(0));
+		}
+
+		let same_direction = columns.iter().all(|c| c.desc == columns[0].desc);
+		if same_direction {
+			f.write_str("(")?;
+			for (i, col) in columns.iter().enumerate() {
+				if i != 0 {
+					f.write_str(", ")?;
+				}
+				write!(f, "{}", col.name)?;
+			}
+			write!(f, ") {} (", op(&columns[0]))?;
+			for i in 0..columns.len() {
+				if i != 0 {
+					f.write_str(", ")?;
+				}
+				write!(f, "${}", param(i))?;
+			}
+			return f.write_str(")");
+		}
+
+		// ("a" > $1 OR ("a" = $1 AND "b" < $2))
+		f.write_str("(")?;
+		for (i, col) in columns.iter().enumerate() {
+			if i != 0 {
+				f.write_str(" OR (")?;
+			}
+			for (j, prev) in columns[..i].iter().enumerate() {
+				write!(f, "{} = ${} AND ", prev.name, param(j))?;
+			}
+			write!(f, "{} {} ${}", col.name, op(col), param(i))?;
+			if i != 0 {
+				f.write_str(")")?;
+			}
+		}
+		f.write_str(")")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use crate::filter::{Filter, OrderByPart};
+
+	#[test]
+	fn test_cursor_roundtrip() {
+		let cursor = Cursor::new(vec![
+			RawValue::encode(&42i32).unwrap(),
+			RawValue::encode(&"abc").unwrap(),
+			RawValue::encode(&Option::<i64>::None).unwrap(),
+		]);
+
+		let s = cursor.to_string();
+		assert_eq!(s.parse::<Cursor>().unwrap(), cursor);
+
+		let json = serde_json::to_string(&cursor).unwrap();
+		assert_eq!(json, format!("\"{s}\""));
+
+		assert!("AAAAFwAAAAQAAA".parse::<Cursor>().is_err());
+		assert!("%%".parse::<Cursor>().is_err());
+	}
+
+	#[test]
+	fn test_keyset() {
+		let created = 1;
+		let id = 2;
+		let cursor = Cursor::new(vec![
+			RawValue::encode(&created).unwrap(),
+			RawValue::encode(&id).unwrap(),
+		]);
+
+		let filter = Filter::new()
+			.and_eq("a", &1)
+			.or_eq("b", &2)
+			.order_desc("created")
+			.order_desc("id")
+			.after(&cursor)
+			.unwrap();
+		assert_eq!(
+			filter.to_string(),
+			r#" WHERE ("a" = $1 OR "b" = $2) AND ("created", "id") < ($3, $4) ORDER BY "created" DESC, "id" DESC"#
+		);
+		assert_eq!(filter.params.len(), 4);
+
+		let filter = Filter::new()
+			.order_desc("created")
+			.order_asc("id")
+			.after(&cursor)
+			.unwrap();
+		assert_eq!(
+			filter.to_string(),
+			r#" WHERE ("created" < $1 OR ("created" = $1 AND "id" > $2)) ORDER BY "created" DESC, "id" ASC"#
+		);
+
+		let res = Filter::new().order_asc("id").after(&cursor);
+		assert!(matches!(res, Err(CursorError::Mismatch { .. })));
+
+		let res = Filter::new().after(&cursor);
+		assert!(matches!(res, Err(CursorError::Unordered)));
+
+		let res = Filter::new()
+			.order_by(OrderByPart::desc("created").nulls_last())
+			.order_asc("id")
+			.after(&cursor);
+		assert!(matches!(res, Err(CursorError::Unordered)));
+	}
+}
@@ -0,0 +1,738 @@
+use std::borrow::Cow;
+use std::mem;
+
+use tokio_postgres::types::ToSql;
+use types::time::{Date, DateTime};
+
+use super::{
+	Direction, Filter, Operator, OrderByPart, Param, ParamData, TextSearch,
+};
+use crate::table::column::ColumnKind;
+use crate::table::{Info, TableTemplate};
+
+/// Parses a filter from a query string like
+/// `?status=active&age_gte=18&sort=-created,name&limit=50` or an equivalent
+/// json object.
+///
+/// Only the allowed columns can be filtered and sorted by, the values are
+/// converted to the [`ColumnKind`] of the column before they are bound, so no
+/// input ever reaches the sql.
+///
+/// A key is either a column which is compared with `=` or a column followed
+/// by `_` and one of the operators `eq`, `ne`, `lt`, `lte`, `gt`, `gte`,
+/// `like`, `ilike`, `in` (comma separated or a json array), `null` (`true` or
+/// `false`) or `search` (tsvector columns only). The keys `sort`, `limit`
+/// and `offset` are reserved.
+///
+/// ## Example
+/// ```
+/// use fire_postgres::filter::FilterParser;
+/// use fire_postgres::table::column::ColumnKind;
+///
+/// let parser = FilterParser::new()
+/// 	.column("status", ColumnKind::Text)
+/// 	.column("age", ColumnKind::I32)
+/// 	.column("created", ColumnKind::Timestamp)
+/// 	.max_limit(100);
+///
+/// let filter = parser
+/// 	.parse_query("status=active&age_gte=18&sort=-created&limit=50")
+/// 	.unwrap();
+/// assert_eq!(
+/// 	filter.to_string(),
+/// 	r#" WHERE "status" = $1 AND "age" >= $2 ORDER BY "created" DESC LIMIT 50"#
+/// );
+///
+/// assert!(parser.parse_query("password=1234").is_err());
+/// assert!(parser.parse_query("age_between=1").is_err());
+/// assert!(parser.parse_query("age=eighteen").is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FilterParser {
+	columns: Vec<(&'static str, ColumnKind)>,
+	max_limit: Option<usize>,
+}
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum QueryError {
+	#[error("Unknown column {0}")]
+	UnknownColumn(String),
+
+	#[error("Unknown operator {0}")]
+	UnknownOperator(String),
+
+	#[error("Operator {operator} is not supported by column {column}")]
+	UnsupportedOperator {
+		column: &'static str,
+		operator: &'static str,
+	},
+
+	#[error("Invalid value {value:?} for {key}")]
+	InvalidValue { key: String, value: String },
+
+	#[error("The limit {limit} is bigger than the maximum {max}")]
+	LimitTooBig { limit: usize, max: usize },
+
+	#[error("Invalid percent encoding in the query string")]
+	InvalidEncoding,
+
+	#[error("Expected a json object")]
+	ExpectedObject,
+}
+
+/// The value of a key, only the `in` operator accepts a list.
+enum Value<'v> {
+	One(Cow<'v, str>),
+	List(Vec<Cow<'v, str>>),
+	// only json has nulls
+	#[cfg_attr(not(feature = "json"), allow(dead_code))]
+	Null,
+}
+
+impl<'v> Value<'v> {
+	fn invalid(&self, key: &str) -> QueryError {
+		let value = match self {
+			Self::One(v) => v.to_string(),
+			Self::List(l) => l.join(","),
+			Self::Null => "null".into(),
+		};
+
+		QueryError::InvalidValue {
+			key: key.into(),
+			value,
+		}
+	}
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+	Eq,
+	Ne,
+	Lt,
+	Lte,
+	Gt,
+	Gte,
+	Like,
+	ILike,
+	In,
+	Null,
+	Search,
+}
+
+impl Op {
+	fn parse(s: &str) -> Option<Self> {
+		Some(match s {
+			"eq" => Self::Eq,
+			"ne" => Self::Ne,
+			"lt" => Self::Lt,
+			"lte" => Self::Lte,
+			"gt" => Self::Gt,
+			"gte" => Self::Gte,
+			"like" => Self::Like,
+			"ilike" => Self::ILike,
+			"in" => Self::In,
+			"null" => Self::Null,
+			"search" => Self::Search,
+			_ => return None,
+		})
+	}
+
+	fn as_str(&self) -> &'static str {
+		match self {
+			Self::Eq => "eq",
+			Self::Ne => "ne",
+			Self::Lt => "lt",
+			Self::Lte => "lte",
+			Self::Gt => "gt",
+			Self::Gte => "gte",
+			Self::Like => "like",
+			Self::ILike => "ilike",
+			Self::In => "in",
+			Self::Null => "null",
+			Self::Search => "search",
+		}
+	}
+}
+
+impl FilterParser {
+	/// Creates a parser which does not allow any column.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Allows all columns of the table.
+	pub fn from_table<T: TableTemplate>() -> Self {
+		Self::from_info(&T::table_info())
+	}
+
+	/// Allows all columns of the info.
+	pub fn from_info(info: &Info) -> Self {
+		Self {
+			columns: info
+				.data()
+				.iter()
+				.map(|col| (col.name, col.kind.clone()))
+				.collect(),
+			max_limit: None,
+		}
+	}
+
+	/// Allows a column.
+	pub fn column(mut self, name: &'static str, kind: ColumnKind) -> Self {
+		self.columns.push((name, kind));
+		self
+	}
+
+	/// Only keeps the columns which are in the list.
+	pub fn only(mut self, names: &[&str]) -> Self {
+		self.columns.retain(|(name, _)| names.contains(name));
+		self
+	}
+
+	/// Returns an error if a bigger limit is requested.
+	pub fn max_limit(mut self, max: usize) -> Self {
+		self.max_limit = Some(max);
+		self
+	}
+
+	/// Parses an url encoded query string, a leading `?` is ignored.
+	pub fn parse_query(
+		&self,
+		query: &str,
+	) -> Result<Filter<'static>, QueryError> {
+		let query = query.strip_prefix('?').unwrap_or(query);
+
+		let mut parsed = Parsed::new();
+		for pair in query.split('&').filter(|pair| !pair.is_empty()) {
+			let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+			let key = percent_decode(key)?;
+
+			// lists are split before decoding so an encoded `,` stays in the
+			// value and a `+` in front of a sort column is not a space
+			let value = if key == "sort" {
+				Value::List(decode_list(value, |part| {
+					part.strip_prefix('+').unwrap_or(part)
+				})?)
+			} else if self.is_list(&key) {
+				Value::List(decode_list(value, |part| part)?)
+			} else {
+				Value::One(percent_decode(value)?)
+			};
+
+			parsed = self.parse_key(parsed, &key, value)?;
+		}
+
+		self.finish(parsed)
+	}
+
+	/// Parses key value pairs which are already decoded, for example from
+	/// the query extractor of a web framework.
+	pub fn parse_pairs<K, V>(
+		&self,
+		pairs: impl IntoIterator<Item = (K, V)>,
+	) -> Result<Filter<'static>, QueryError>
+	where
+		K: AsRef<str>,
+		V: AsRef<str>,
+	{
+		let mut parsed = Parsed::new();
+		for (key, value) in pairs {
+			let (key, value) = (key.as_ref(), value.as_ref());
+			let value = if self.is_list(key) {
+				Value::List(value.split(',').map(Cow::Borrowed).collect())
+			} else {
+				Value::One(Cow::Borrowed(value))
+			};
+
+			parsed = self.parse_key(parsed, key, value)?;
+		}
+
+		self.finish(parsed)
+	}
+
+	/// Parses a json object, numbers and booleans can be used for the
+	/// values, `null` compares with `IS NULL` and `in` needs an array.
+	#[cfg(feature = "json")]
+	pub fn parse_json(
+		&self,
+		json: &serde_json::Value,
+	) -> Result<Filter<'static>, QueryError> {
+		use serde_json::Value as Json;
+
+		fn scalar(json: &Json) -> Option<Cow<'_, str>> {
+			match json {
+				Json::String(s) => Some(Cow::Borrowed(s)),
+				Json::Number(n) => Some(Cow::Owned(n.to_string())),
+				Json::Bool(b) => Some(Cow::Owned(b.to_string())),
+				_ => None,
+			}
+		}
+
+		let Json::Object(obj) = json else {
+			return Err(QueryError::ExpectedObject);
+		};
+
+		let mut parsed = Parsed::new();
+		for (key, json) in obj {
+			let value = match json {
+				Json::Null => Value::Null,
+				Json::Array(list) => Value::List(
+					list.iter()
+						.map(|v| scalar(v).ok_or_else(|| invalid(key, v)))
+						.collect::<Result<_, _>>()?,
+				),
+				v => Value::One(scalar(v).ok_or_else(|| invalid(key, v))?),
+			};
+
+			parsed = self.parse_key(parsed, key, value)?;
+		}
+
+		self.finish(parsed)
+	}
+
+	fn is_column(&self, name: &str) -> bool {
+		self.find(name).is_some()
+	}
+
+	/// The `in` operator takes a comma separated list.
+	fn is_list(&self, key: &str) -> bool {
+		key.ends_with("_in") && !self.is_column(key)
+	}
+
+	fn find(&self, name: &str) -> Option<&(&'static str, ColumnKind)> {
+		self.columns.iter().find(|(col, _)| *col == name)
+	}
+
+	fn parse_key(
+		&self,
+		mut parsed: Parsed,
+		key: &str,
+		value: Value,
+	) -> Result<Parsed, QueryError> {
+		match key {
+			"sort" => {
+				let parts: Vec<&str> = match &value {
+					Value::One(sort) => sort.split(',').collect(),
+					Value::List(list) => list.iter().map(|p| &**p).collect(),
+					Value::Null => return Err(value.invalid(key)),
+				};
+				for part in parts.into_iter().filter(|p| !p.is_empty()) {
+					parsed.order_by.push(self.parse_sort(part)?);
+				}
+			}
+			"limit" => {
+				let limit = parse_usize(key, &value)?;
+				if let Some(max) = self.max_limit.filter(|max| limit > *max) {
+					return Err(QueryError::LimitTooBig { limit, max });
+				}
+				parsed.limit = Some(limit);
+			}
+			"offset" => parsed.offset = Some(parse_usize(key, &value)?),
+			_ => {
+				let (column, kind, op) = self.parse_column(key)?;
+				let filter = mem::replace(&mut parsed.filter, Filter::new());
+				parsed.filter =
+					condition(filter, key, column, kind, op, value)?;
+			}
+		}
+
+		Ok(parsed)
+	}
+
+	/// A column followed by an optional operator, a column which contains an
+	/// `_` is always preferred.
+	fn parse_column(
+		&self,
+		key: &str,
+	) -> Result<(&'static str, &ColumnKind, Op), QueryError> {
+		if let Some((name, kind)) = self.find(key) {
+			return Ok((name, kind, Op::Eq));
+		}
+
+		let (column, op) = key
+			.rsplit_once('_')
+			.ok_or_else(|| QueryError::UnknownColumn(key.into()))?;
+		let (name, kind) = self
+			.find(column)
+			.ok_or_else(|| QueryError::UnknownColumn(column.into()))?;
+		let op = Op::parse(op)
+			.ok_or_else(|| QueryError::UnknownOperator(op.into()))?;
+
+		Ok((name, kind, op))
+	}
+
+	/// `column` is ascending and `-column` descending.
+	fn parse_sort(&self, part: &str) -> Result<OrderByPart, QueryError> {
+		let (column, direction) = match part.strip_prefix('-') {
+			Some(column) => (column, Direction::Desc),
+			None => (part.strip_prefix('+').unwrap_or(part), Direction::Asc),
+		};
+
+		match self.find(column) {
+			Some((name, kind)) if is_search(kind) => {
+				Err(QueryError::UnsupportedOperator {
+					column: name,
+					operator: "sort",
+				})
+			}
+			Some((name, _)) => Ok(OrderByPart::column(*name, direction)),
+			None => Err(QueryError::UnknownColumn(column.into())),
+		}
+	}
+
+	fn finish(&self, parsed: Parsed) -> Result<Filter<'static>, QueryError> {
+		let mut filter = parsed.filter;
+		for part in parsed.order_by {
+			filter = filter.order_by(part);
+		}
+		if let Some(limit) = parsed.limit.or(self.max_limit) {
+			filter = filter.limit(limit);
+		}
+		if let Some(offset) = parsed.offset {
+			filter = filter.offset(offset);
+		}
+
+		Ok(filter)
+	}
+}
+
+struct Parsed {
+	filter: Filter<'static>,
+	order_by: Vec<OrderByPart>,
+	limit: Option<usize>,
+	offset: Option<usize>,
+}
+
+impl Parsed {
+	fn new() -> Self {
+		Self {
+			filter: Filter::new(),
+			order_by: vec![],
+			limit: None,
+			offset: None,
+		}
+	}
+}
+
+fn condition(
+	filter: Filter<'static>,
+	key: &str,
+	column: &'static str,
+	kind: &ColumnKind,
+	op: Op,
+	value: Value,
+) -> Result<Filter<'static>, QueryError> {
+	let unsupported = || QueryError::UnsupportedOperator {
+		column,
+		operator: op.as_str(),
+	};
+
+	let (kind, nullable) = match kind {
+		ColumnKind::Option(kind) => (&**kind, true),
+		kind => (kind, false),
+	};
+
+	// a tsvector can only be searched
+	if is_search(kind) != matches!(op, Op::Search) {
+		return Err(unsupported());
+	}
+
+	// a single json value is a list with one element, like `age_in=1` in a
+	// query string
+	let value = match (op, value) {
+		(Op::In, Value::One(v)) => Value::List(vec![v]),
+		(_, value) => value,
+	};
+
+	let operator = match (op, &value) {
+		// a null param is changed to `IS NULL` or `IS NOT NULL`
+		(Op::Eq | Op::Ne, Value::Null) | (Op::Null, _) if !nullable => {
+			return Err(unsupported())
+		}
+		(Op::Eq, Value::Null) => {
+			return Ok(filter.and_param(Operator::Eq, null(column)))
+		}
+		(Op::Ne, Value::Null) => {
+			return Ok(filter.and_param(Operator::Ne, null(column)))
+		}
+		(Op::Null, value) => {
+			let operator = match value {
+				Value::One(v) if v == "true" => Operator::Eq,
+				Value::One(v) if v == "false" => Operator::Ne,
+				_ => return Err(value.invalid(key)),
+			};
+			return Ok(filter.and_param(operator, null(column)));
+		}
+		(_, Value::Null) => return Err(value.invalid(key)),
+		(Op::In, _) => Operator::Any,
+		(_, Value::List(_)) => return Err(value.invalid(key)),
+		(Op::Like | Op::ILike, _) if !is_text(kind) => {
+			return Err(unsupported())
+		}
+		(Op::Search, _) => Operator::TextSearch(TextSearch::websearch()),
+		(Op::Eq, _) => Operator::Eq,
+		(Op::Ne, _) => Operator::Ne,
+		(Op::Lt, _) => Operator::Lt,
+		(Op::Lte, _) => Operator::Lte,
+		(Op::Gt, _) => Operator::Gt,
+		(Op::Gte, _) => Operator::Gte,
+		(Op::Like, _) => Operator::Like,
+		(Op::ILike, _) => Operator::ILike,
+	};
+
+	let param = match kind {
+		ColumnKind::Boolean => param(key, column, value, parse_bool),
+		ColumnKind::Varchar(_)
+		| ColumnKind::FixedText(_)
+		| ColumnKind::Text
+		| ColumnKind::TsVector
+		| ColumnKind::GeneratedTsVector { .. } => {
+			param(key, column, value, |v| Some(v.to_string()))
+		}
+		ColumnKind::Date => {
+			param(key, column, value, |v| v.parse::<Date>().ok())
+		}
+		ColumnKind::Timestamp => {
+			param(key, column, value, |v| DateTime::parse_from_iso8601(v).ok())
+		}
+		ColumnKind::F64 => param(key, column, value, |v| v.parse::<f64>().ok()),
+		ColumnKind::F32 => param(key, column, value, |v| v.parse::<f32>().ok()),
+		ColumnKind::I64 => param(key, column, value, |v| v.parse::<i64>().ok()),
+		ColumnKind::I32 => param(key, column, value, |v| v.parse::<i32>().ok()),
+		ColumnKind::I16 => param(key, column, value, |v| v.parse::<i16>().ok()),
+		ColumnKind::Option(_)
+		| ColumnKind::TextArray
+		| ColumnKind::Bytea
+		| ColumnKind::Json => return Err(unsupported()),
+	}?;
+
+	Ok(filter.and_param(operator, param))
+}
+
+/// Converts the value, a list is bound as an array.
+fn param<T>(
+	key: &str,
+	column: &'static str,
+	value: Value,
+	parse: impl Fn(&str) -> Option<T>,
+) -> Result<Param<'static>, QueryError>
+where
+	T: ParamData + ToSql + Send + Sync + 'static,
+{
+	let parsed = match &value {
+		Value::One(v) => parse(v).map(|v| Param::new_owned(column, v)),
+		Value::List(list) => list
+			.iter()
+			.map(|v| parse(v))
+			.collect::<Option<Vec<_>>>()
+			.map(|v| Param::new_owned(column, v)),
+		Value::Null => None,
+	};
+
+	parsed.ok_or_else(|| value.invalid(key))
+}
+
+fn null(column: &'static str) -> Param<'static> {
+	Param::new_owned(column, None::<String>)
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+	match value {
+		"true" => Some(true),
+		"false" => Some(false),
+		_ => None,
+	}
+}
+
+fn parse_usize(key: &str, value: &Value) -> Result<usize, QueryError> {
+	match value {
+		Value::One(v) => v.parse().ok(),
+		_ => None,
+	}
+	.ok_or_else(|| value.invalid(key))
+}
+
+fn is_text(kind: &ColumnKind) -> bool {
+	matches!(
+		kind,
+		ColumnKind::Varchar(_) | ColumnKind::FixedText(_) | ColumnKind::Text
+	)
+}
+
+fn is_search(kind: &ColumnKind) -> bool {
+	let kind = match kind {
+		ColumnKind::Option(kind) => kind,
+		kind => kind,
+	};
+
+	matches!(
+		kind,
+		ColumnKind::TsVector | ColumnKind::GeneratedTsVector { .. }
+	)
+}
+
+#[cfg(feature = "json")]
+fn invalid(key: &str, json: &serde_json::Value) -> QueryError {
+	QueryError::InvalidValue {
+		key: key.into(),
+		value: json.to_string(),
+	}
+}
+
+/// Decodes `%XX` and `+` as a space.
+/// Splits a list on `,` and decodes every part after `map`.
+fn decode_list<'v>(
+	value: &'v str,
+	map: impl Fn(&'v str) -> &'v str,
+) -> Result<Vec<Cow<'v, str>>, QueryError> {
+	value
+		.split(',')
+		.map(|part| percent_decode(map(part)))
+		.collect()
+}
+
+fn percent_decode(s: &str) -> Result<Cow<'_, str>, QueryError> {
+	if !s.contains(['%', '+']) {
+		return Ok(Cow::Borrowed(s));
+	}
+
+	let mut bytes = Vec::with_capacity(s.len());
+	let mut iter = s.bytes();
+	while let Some(b) = iter.next() {
+		match b {
+			b'+' => bytes.push(b' '),
+			b'%' => {
+				let hex = [iter.next(), iter.next()];
+				let [Some(h), Some(l)] = hex else {
+					return Err(QueryError::InvalidEncoding);
+				};
+				let hex = std::str::from_utf8(&[h, l])
+					.ok()
+					.and_then(|hex| u8::from_str_radix(hex, 16).ok())
+					.ok_or(QueryError::InvalidEncoding)?;
+				bytes.push(hex);
+			}
+			b => bytes.push(b),
+		}
+	}
+
+	String::from_utf8(bytes)
+		.map(Cow::Owned)
+		.map_err(|_| QueryError::InvalidEncoding)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn parser() -> FilterParser {
+		FilterParser::new()
+			.column("status", ColumnKind::Text)
+			.column("age", ColumnKind::Option(Box::new(ColumnKind::I32)))
+			.column("created_at", ColumnKind::Timestamp)
+			.column("search", ColumnKind::TsVector)
+			.max_limit(100)
+	}
+
+	#[test]
+	fn test_parse_query() {
+		let filter = parser()
+			.parse_query(
+				"?status_ilike=%25act%2B&age_in=1,2&age_null=false\
+				&created_at_gte=2024-01-01T00:00:00Z&search_search=a+b\
+				&sort=-created_at,age&offset=20",
+			)
+			.unwrap();
+		assert_eq!(
+			filter.to_string(),
+			r#" WHERE "status" ILIKE $1 AND "age" = ANY($2) AND "age" IS NOT NULL AND "created_at" >= $3 AND "search" @@ websearch_to_tsquery($4) ORDER BY "created_at" DESC, "age" ASC LIMIT 100 OFFSET 20"#
+		);
+		assert_eq!(filter.params.len(), 4);
+	}
+
+	#[test]
+	fn test_parse_errors() {
+		let parser = parser();
+		let err = |query| parser.parse_query(query).unwrap_err();
+
+		assert!(matches!(err("name=a"), QueryError::UnknownColumn(_)));
+		assert!(matches!(
+			err("status_max=a"),
+			QueryError::UnknownOperator(_)
+		));
+		assert!(matches!(err("sort=name"), QueryError::UnknownColumn(_)));
+		assert!(matches!(
+			err("age_like=1"),
+			QueryError::UnsupportedOperator { .. }
+		));
+		assert!(matches!(
+			err("status_null=true"),
+			QueryError::UnsupportedOperator { .. }
+		));
+		assert!(matches!(
+			err("search=a"),
+			QueryError::UnsupportedOperator { .. }
+		));
+		assert!(matches!(err("age_in=1,a"), QueryError::InvalidValue { .. }));
+		assert!(matches!(
+			err("created_at=yesterday"),
+			QueryError::InvalidValue { .. }
+		));
+		assert!(matches!(err("limit=101"), QueryError::LimitTooBig { .. }));
+		assert!(matches!(err("status=%zz"), QueryError::InvalidEncoding));
+	}
+
+	#[test]
+	fn test_parse_query_encoded_lists() {
+		let filter = parser()
+			.parse_query("status_in=a%2Cb,c&sort=+age,-created_at")
+			.unwrap();
+		assert_eq!(
+			filter.to_debug_sql(),
+			r#" WHERE "status" = ANY(ARRAY['a,b', 'c']::text[]) ORDER BY "age" ASC, "created_at" DESC LIMIT 100"#
+		);
+	}
+
+	#[test]
+	fn test_only() {
+		let parser = parser().only(&["age"]);
+		assert!(parser.parse_query("age=1").is_ok());
+		assert!(matches!(
+			parser.parse_query("status=a").unwrap_err(),
+			QueryError::UnknownColumn(_)
+		));
+	}
+
+	#[cfg(feature = "json")]
+	#[test]
+	fn test_parse_json() {
+		let json = serde_json::json!({
+			"status": "active",
+			"age": null,
+			"age_in": [1, 2],
+			"sort": "-age",
+			"limit": 10
+		});
+
+		// the keys of a json object are sorted
+		let filter = parser().parse_json(&json).unwrap();
+		assert_eq!(
+			filter.to_string(),
+			r#" WHERE "age" IS NULL AND "age" = ANY($1) AND "status" = $2 ORDER BY "age" DESC LIMIT 10"#
+		);
+
+		let json = serde_json::json!({ "age_in": 1 });
+		let filter = parser().parse_json(&json).unwrap();
+		assert_eq!(
+			crate::filter::DebugSql::new().filter(&filter),
+			r#" WHERE "age" = ANY(ARRAY[1]::int4[]) LIMIT 100"#
+		);
+
+		let json = serde_json::json!({ "age": [1] });
+		assert!(matches!(
+			parser().parse_json(&json).unwrap_err(),
+			QueryError::InvalidValue { .. }
+		));
+	}
+}
@@ -0,0 +1,124 @@
+use std::fmt;
+
+use super::SqlStr;
+
+/// The rust type of a tsvector column, it is searched with a text value.
+///
+/// Only used as the type of a [`Col`](super::Col), a tsvector can not be
+/// read or written.
+#[derive(Debug)]
+pub enum TsVector {}
+
+/// A full text search query, compared to a tsvector column with `@@`.
+///
+/// ## Example
+/// ```
+/// use fire_postgres::filter::TextSearch;
+///
+/// let search = TextSearch::websearch().language("english");
+/// assert_eq!(
+/// 	search.to_sql(1),
+/// 	"websearch_to_tsquery('english', $1)"
+/// );
+///
+/// assert_eq!(TextSearch::plain().to_sql(2), "plainto_tsquery($2)");
+/// ```
+#[derive(Debug, Clone)]
+pub struct TextSearch {
+	pub query: TsQuery,
+	/// The text search configuration, if none is set the configuration
+	/// `default_text_search_config` is used.
+	pub language: Option<SqlStr>,
+}
+
+/// The function which converts the search text into a tsquery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TsQuery {
+	/// `plainto_tsquery` all words need to match
+	Plain,
+	/// `phraseto_tsquery` the words need to match in the given order
+	Phrase,
+	/// `websearch_to_tsquery` supports `"quoted text"`, `or` and `-word`
+	Websearch,
+	/// `to_tsquery` the value needs to be in the tsquery syntax
+	Raw,
+}
+
+impl TextSearch {
+	pub fn new(query: TsQuery) -> Self {
+		Self {
+			query,
+			language: None,
+		}
+	}
+
+	pub fn plain() -> Self {
+		Self::new(TsQuery::Plain)
+	}
+
+	pub fn phrase() -> Self {
+		Self::new(TsQuery::Phrase)
+	}
+
+	pub fn websearch() -> Self {
+		Self::new(TsQuery::Websearch)
+	}
+
+	pub fn raw() -> Self {
+		Self::new(TsQuery::Raw)
+	}
+
+	/// Sets the text search configuration, for example `english`.
+	///
+	/// This should be the same configuration which was used to create the
+	/// tsvector.
+	pub fn language(mut self, language: impl Into<SqlStr>) -> Self {
+		self.language = Some(language.into());
+		self
+	}
+
+	/// Returns the sql to create the tsquery from the param `$param_num`.
+	pub fn to_sql(&self, param_num: usize) -> String {
+		self.to_formatter(format_args!("${param_num}")).to_string()
+	}
+
+	/// The text is the sql expression of the search text, most of the time
+	/// a param.
+	pub(crate) fn to_formatter<T>(&self, text: T) -> TextSearchFormatter<'_, T>
+	where
+		T: fmt::Display,
+	{
+		TextSearchFormatter { search: self, text }
+	}
+}
+
+impl TsQuery {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			Self::Plain => "plainto_tsquery",
+			Self::Phrase => "phraseto_tsquery",
+			Self::Websearch => "websearch_to_tsquery",
+			Self::Raw => "to_tsquery",
+		}
+	}
+}
+
+pub(crate) struct TextSearchFormatter<'a, T> {
+	search: &'a TextSearch,
+	text: T,
+}
+
+impl<T> fmt::Display for TextSearchFormatter<'_, T>
+where
+	T: fmt::Display,
+{
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}(", self.search.query.as_str())?;
+
+		if let Some(language) = &self.search.language {
+			write!(f, "'{}', ", language.replace('\'', "''"))?;
+		}
+
+		write!(f, "{})", self.text)
+	}
+}
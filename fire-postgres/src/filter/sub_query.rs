@@ -0,0 +1,95 @@
+use std::fmt;
+
+use super::{fmt_where, Where};
+use crate::Ident;
+
+/// A sub query `SELECT "column" FROM "table" WHERE ...`, used for `IN` and
+/// `EXISTS` conditions.
+///
+/// The params of the where clause are part of the outer filter.
+///
+/// ## Example
+/// ```
+/// use fire_postgres::filter::{Comparison, Filter, WhereFilter};
+///
+/// let active = true;
+/// let filter = Filter::new()
+/// 	.and_in_select(
+/// 		"user_id",
+/// 		"users",
+/// 		"id",
+/// 		WhereFilter::new().and_eq("active", &active),
+/// 	)
+/// 	.and_exists(
+/// 		"orders",
+/// 		// without a table prefix the columns refer to the inner table
+/// 		WhereFilter::new().and_sql("id", Comparison::Eq, r#""posts"."order_id""#),
+/// 	);
+///
+/// assert_eq!(
+/// 	filter.to_string(),
+/// 	" WHERE \"user_id\" = ANY(SELECT \"id\" FROM \"users\" WHERE \
+/// 	\"active\" = $1) AND EXISTS (SELECT 1 FROM \"orders\" WHERE \
+/// 	\"id\" = \"posts\".\"order_id\")"
+/// );
+/// ```
+#[derive(Debug)]
+pub struct SubQuery {
+	pub table: Ident,
+	/// If no column is set `1` is selected
+	pub column: Option<Ident>,
+	pub whr: Where,
+}
+
+impl SubQuery {
+	pub fn new(
+		table: impl Into<Ident>,
+		column: Option<Ident>,
+		whr: Where,
+	) -> Self {
+		Self {
+			table: table.into(),
+			column,
+			whr,
+		}
+	}
+
+	/// Returns how many params the where clause needs.
+	pub fn param_count(&self) -> usize {
+		self.whr.param_count()
+	}
+
+	pub(crate) fn to_formatter(
+		&self,
+		param_start: usize,
+	) -> SubQueryFormatter<'_> {
+		SubQueryFormatter {
+			query: self,
+			param_start,
+		}
+	}
+}
+
+/// Writes the sub query without parentheses.
+pub(crate) struct SubQueryFormatter<'a> {
+	query: &'a SubQuery,
+	param_start: usize,
+}
+
+impl fmt::Display for SubQueryFormatter<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match &self.query.column {
+			Some(column) => write!(f, "SELECT {}", column)?,
+			None => f.write_str("SELECT 1")?,
+		}
+
+		write!(f, " FROM {}", self.query.table)?;
+
+		if !self.query.whr.is_empty() {
+			f.write_str(" WHERE ")?;
+			fmt_where(&self.query.whr, f, &mut self.param_start.clone())?;
+		}
+
+		Ok(())
+	}
+}
@@ -1,8 +1,36 @@
 /// Possible operators
-/// - = | != | < | <= | > | >= | LIKE | IN
+/// - = | != | < | <= | > | >= | LIKE | ILIKE | NOT LIKE | NOT ILIKE | IN
+/// - REGEX | ~* | !~ | !~* | SIMILAR TO
+/// - = ANY | != ALL | @> | <@ | && (with array values)
+/// - ? (json key exists)
+/// - @@ | @@ ("english") | @@ PLAIN | @@ PLAIN ("english") (full text search
+///   with `websearch_to_tsquery` or `plainto_tsquery`)
+/// - % | %> | SIMILARITY("col", value) > 0.3 |
+///   WORD_SIMILARITY("col", value) > 0.3 (trigram similarity)
+/// - "col"->"key" | "col"->>"key" | "col"->0 | "col"#>["a", "b"] |
+///   "col"#>>["a", "b"] (json paths before any operator)
+/// - "col" > "other_col" (a literal is another column)
+/// - "col" < SQL("now()") (a sql expression, which is not escaped)
+/// - "col" IN (SELECT "id" FROM "table" WHERE ...) |
+///   EXISTS (SELECT 1 FROM "table" WHERE ...) | NOT EXISTS (...)
+/// - User::EMAIL = value (a typed column, see [`Col`](crate::filter::Col))
 /// - AND | OR
+/// - ( ... ) | NOT ( ... )
 ///
-/// ~ | ~= | =~ are shortcuts for LIKE
+/// ~ | ~= | =~ are shortcuts for LIKE, with the value escaped and wrapped in
+/// `%val%`, `%val` or `val%`
+///
+/// IN binds all values as one array `"col" = ANY($1)`, so the same statement
+/// can be used for any number of values
+///
+/// ORDER "col" ASC | "col" DESC | "col" dir (a [`Direction`] variable) |
+/// SQL("lower(\"name\")") ASC, each can be followed by NULLS FIRST or
+/// NULLS LAST
+///
+/// ORDER "col" @@ value orders by `ts_rank`, the most relevant rows first,
+/// ORDER "col" <-> value by the trigram distance, the most similar rows first
+///
+/// [`Direction`]: crate::filter::Direction
 /// ## Example
 /// ```
 /// use fire_postgres::filter;
@@ -12,20 +40,23 @@
 /// let query = filter!(&a AND "b" != &b OR &c ORDER "a" ASC "b" DESC);
 ///
 /// assert_eq!(r#" WHERE "a" = $1 AND "b" != $2 OR "c" IS NULL ORDER BY "a" ASC, "b" DESC"#, query.to_string());
+///
+/// let query = filter!(&a AND NOT ("b" = &b OR &c) LIMIT 10);
+/// assert_eq!(r#" WHERE "a" = $1 AND NOT ("b" = $2 OR "c" IS NULL) LIMIT 10"#, query.to_string());
 /// ```
 #[macro_export]
 macro_rules! filter {
 	// order
-	(cont; $f:ident, ORDER $value:tt $($tt:tt)*) => ({
-		$crate::filter_order!($f, $value);
+	(cont; $f:ident, ORDER $($tt:tt)+) => ({
+		$crate::filter_order!($f, $($tt)+);
 	});
 	// limit
-	(cont; $f:ident, LIMIT $value:tt $($tt:tt)*) => ({
-		$crate::filter_limit!($f, $value);
+	(cont; $f:ident, LIMIT $($tt:tt)+) => ({
+		$crate::filter_limit!($f, $($tt)+);
 	});
 	// offset
-	(cont; $f:ident, OFFSET $value:tt $($tt:tt)*) => ({
-		$crate::filter_offset!($f, $value);
+	(cont; $f:ident, OFFSET $($tt:tt)+) => ({
+		$crate::filter_offset!($f, $($tt)+);
 	});
 	(cont; $f:ident, $($tt:tt)*) => ({
 		$crate::filter_inner!($f, $($tt)*);
@@ -41,10 +72,29 @@ macro_rules! filter {
 }
 
 /// Possible operators
-/// - = | != | < | <= | > | >= | LIKE | IN
+/// - = | != | < | <= | > | >= | LIKE | ILIKE | NOT LIKE | NOT ILIKE | IN
+/// - REGEX | ~* | !~ | !~* | SIMILAR TO
+/// - = ANY | != ALL | @> | <@ | && (with array values)
+/// - ? (json key exists)
+/// - @@ | @@ ("english") | @@ PLAIN | @@ PLAIN ("english") (full text search
+///   with `websearch_to_tsquery` or `plainto_tsquery`)
+/// - % | %> | SIMILARITY("col", value) > 0.3 |
+///   WORD_SIMILARITY("col", value) > 0.3 (trigram similarity)
+/// - "col"->"key" | "col"->>"key" | "col"->0 | "col"#>["a", "b"] |
+///   "col"#>>["a", "b"] (json paths before any operator)
+/// - "col" > "other_col" (a literal is another column)
+/// - "col" < SQL("now()") (a sql expression, which is not escaped)
+/// - "col" IN (SELECT "id" FROM "table" WHERE ...) |
+///   EXISTS (SELECT 1 FROM "table" WHERE ...) | NOT EXISTS (...)
+/// - User::EMAIL = value (a typed column, see [`Col`](crate::filter::Col))
 /// - AND | OR
+/// - ( ... ) | NOT ( ... )
+///
+/// ~ | ~= | =~ are shortcuts for LIKE, with the value escaped and wrapped in
+/// `%val%`, `%val` or `val%`
 ///
-/// ~ | ~= | =~ are shortcuts for LIKE
+/// IN binds all values as one array `"col" = ANY($1)`, so the same statement
+/// can be used for any number of values
 /// ## Example
 /// ```
 /// use fire_postgres::whr;
@@ -71,152 +121,506 @@ macro_rules! whr {
 macro_rules! filter_inner {
 	($f:ident,) => ();
 
+	// not group, needs to be before ident eq
+	($f:ident, NOT ($($inner:tt)*) $($tt:tt)*) => (
+		$crate::filter_group!($f, Not, $($inner)*);
+		$crate::whr_log!($f, $($tt)*);
+	);
+	// not exists, needs to be before ident eq
+	(
+		$f:ident,
+		NOT EXISTS (SELECT 1 FROM $table:literal $($inner:tt)*)
+		$($tt:tt)*
+	) => (
+		{
+			let mut g = $crate::filter::WhereFilter::new();
+			$crate::filter_sub_query!(g, [EXISTS], $table, [$($inner)*]);
+
+			$f.whr.push($crate::filter::WherePart::Not(g.whr));
+			$f.params.append(g.params);
+		}
+		$crate::whr_log!($f, $($tt)*);
+	);
+	// exists, needs to be before ident eq
+	(
+		$f:ident,
+		EXISTS (SELECT 1 FROM $table:literal $($inner:tt)*)
+		$($tt:tt)*
+	) => (
+		$crate::filter_sub_query!($f, [EXISTS], $table, [$($inner)*]);
+		$crate::whr_log!($f, $($tt)*);
+	);
+	// group
+	($f:ident, ($($inner:tt)*) $($tt:tt)*) => (
+		$crate::filter_group!($f, Nested, $($inner)*);
+		$crate::whr_log!($f, $($tt)*);
+	);
+
+	// trigram similarity, needs to be before ident eq
+	(
+		$f:ident,
+		SIMILARITY($name:literal, $($value:tt)+) > $threshold:literal
+		$($tt:tt)*
+	) => (
+		let param = $crate::filter::Param::new($name, $($value)+);
+		$crate::whr_comp!(
+			fin; $f, param, None,
+			$crate::filter::Operator::Similarity { threshold: $threshold },
+			$($tt)*
+		);
+	);
+	(
+		$f:ident,
+		WORD_SIMILARITY($name:literal, $($value:tt)+) > $threshold:literal
+		$($tt:tt)*
+	) => (
+		let param = $crate::filter::Param::new($name, $($value)+);
+		$crate::whr_comp!(
+			fin; $f, param, None,
+			$crate::filter::Operator::WordSimilarity { threshold: $threshold },
+			$($tt)*
+		);
+	);
+
+	// typed column, needs to be before ident eq
+	($f:ident, $ty:ident :: $col:ident $($tt:tt)+) => (
+		$crate::filter_op!($f, [$ty::$col, None] $($tt)+);
+	);
 	// reference ident eq
 	($f:ident, &$id:ident $($tt:tt)*) => (
-		$crate::whr_comp!($f, stringify!($id), Eq, &$id $($tt)*);
+		$crate::whr_comp!($f, [stringify!($id), None], Eq, &$id $($tt)*);
 	);
 	// ident eq
 	($f:ident, $id:ident $($tt:tt)*) => (
-		$crate::whr_comp!($f, stringify!($id), Eq, $id $($tt)*);
+		$crate::whr_comp!($f, [stringify!($id), None], Eq, $id $($tt)*);
 	);
 
+	// column qualified with a table or alias
+	($f:ident, $table:literal . $name:literal $($tt:tt)+) => (
+		$crate::filter_op!(
+			$f, [$crate::Ident::qualified($table, $name), None] $($tt)+
+		);
+	);
+	// json path
+	($f:ident, $name:literal -> $($tt:tt)+) => (
+		$crate::filter_json!(start; $f, $name, -> $($tt)+);
+	);
+	// json path
+	($f:ident, $name:literal # $($tt:tt)+) => (
+		$crate::filter_json!(start; $f, $name, # $($tt)+);
+	);
+	($f:ident, $name:literal $($tt:tt)+) => (
+		$crate::filter_op!($f, [$name, None] $($tt)+);
+	);
+}
+
+/// Parses the operator, the column is passed as `[name, path]`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! filter_op {
+	// any, needs to be before eq
+	($f:ident, $col:tt = ANY $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, Any, $($tt)*);
+	);
+	// ne all, needs to be before ne
+	($f:ident, $col:tt != ALL $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, NeAll, $($tt)*);
+	);
+	// full text search
+	($f:ident, $col:tt @@ PLAIN ($lang:literal) $($tt:tt)+) => (
+		$crate::whr_comp!(
+			$f, $col,
+			[@@ $crate::filter::TextSearch::plain().language($lang)],
+			$($tt)*
+		);
+	);
+	($f:ident, $col:tt @@ PLAIN $($tt:tt)+) => (
+		$crate::whr_comp!(
+			$f, $col, [@@ $crate::filter::TextSearch::plain()], $($tt)*
+		);
+	);
+	($f:ident, $col:tt @@ ($lang:literal) $($tt:tt)+) => (
+		$crate::whr_comp!(
+			$f, $col,
+			[@@ $crate::filter::TextSearch::websearch().language($lang)],
+			$($tt)*
+		);
+	);
+	($f:ident, $col:tt @@ $($tt:tt)+) => (
+		$crate::whr_comp!(
+			$f, $col, [@@ $crate::filter::TextSearch::websearch()], $($tt)*
+		);
+	);
+	// word similarity, needs to be before similarity
+	($f:ident, $col:tt %> $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, WordSimilar, $($tt)*);
+	);
+	// similarity
+	($f:ident, $col:tt % $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, Similar, $($tt)*);
+	);
+	// contained by, needs to be before lt
+	($f:ident, $col:tt <@ $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, ContainedBy, $($tt)*);
+	);
+	// contains
+	($f:ident, $col:tt @> $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, Contains, $($tt)*);
+	);
+	// overlaps
+	($f:ident, $col:tt && $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, Overlaps, $($tt)*);
+	);
+	// like %val, needs to be before eq and like %val%
+	($f:ident, $col:tt ~= $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, [~=], $($tt)*);
+	);
+	// like val%, needs to be before eq
+	($f:ident, $col:tt =~ $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, [=~], $($tt)*);
+	);
 	// eq
-	($f:ident, $name:literal = $($tt:tt)+) => (
-		$crate::whr_comp!($f, $name, Eq, $($tt)*);
+	($f:ident, $col:tt = $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, Eq, $($tt)*);
 	);
 	// ne
-	($f:ident, $name:literal != $($tt:tt)+) => (
-		$crate::whr_comp!($f, $name, Ne, $($tt)*);
+	($f:ident, $col:tt != $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, Ne, $($tt)*);
 	);
 	// lt
-	($f:ident, $name:literal < $($tt:tt)+) => (
-		$crate::whr_comp!($f, $name, Lt, $($tt)*);
+	($f:ident, $col:tt < $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, Lt, $($tt)*);
 	);
 	// lte
-	($f:ident, $name:literal <= $($tt:tt)+) => (
-		$crate::whr_comp!($f, $name, Lte, $($tt)*);
+	($f:ident, $col:tt <= $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, Lte, $($tt)*);
 	);
 	// gt
-	($f:ident, $name:literal > $($tt:tt)+) => (
-		$crate::whr_comp!($f, $name, Gt, $($tt)*);
+	($f:ident, $col:tt > $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, Gt, $($tt)*);
 	);
 	// gte
-	($f:ident, $name:literal >= $($tt:tt)+) => (
-		$crate::whr_comp!($f, $name, Gte, $($tt)*);
+	($f:ident, $col:tt >= $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, Gte, $($tt)*);
 	);
 	// like
-	($f:ident, $name:literal LIKE $($tt:tt)+) => (
-		$crate::whr_comp!($f, $name, Like, $($tt)*);
+	($f:ident, $col:tt LIKE $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, Like, $($tt)*);
+	);
+	// ilike
+	($f:ident, $col:tt ILIKE $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, ILike, $($tt)*);
+	);
+	// not like
+	($f:ident, $col:tt NOT LIKE $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, NotLike, $($tt)*);
+	);
+	// not ilike
+	($f:ident, $col:tt NOT ILIKE $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, NotILike, $($tt)*);
+	);
+	// similar to
+	($f:ident, $col:tt SIMILAR TO $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, SimilarTo, $($tt)*);
+	);
+	// regex, ~ is already used as a shortcut for like
+	($f:ident, $col:tt REGEX $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, Regex, $($tt)*);
+	);
+	// case insensitive regex, needs to be before like %val%
+	($f:ident, $col:tt ~* $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, IRegex, $($tt)*);
+	);
+	// not case insensitive regex
+	($f:ident, $col:tt !~* $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, NotIRegex, $($tt)*);
+	);
+	// not regex
+	($f:ident, $col:tt !~ $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, NotRegex, $($tt)*);
 	);
 	// like %val%
-	($f:ident, $name:literal ~ $($tt:tt)+) => (
-		$crate::whr_comp!($f, $name, ~, $($tt)*);
+	($f:ident, $col:tt ~ $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, [~], $($tt)*);
 	);
-	// like %val
-	($f:ident, $name:literal ~= $($tt:tt)+) => (
-		$crate::whr_comp!($f, $name, ~=, $($tt)*);
+	// has key
+	($f:ident, $col:tt ? $($tt:tt)+) => (
+		$crate::whr_comp!($f, $col, HasKey, $($tt)*);
 	);
-	// like val%
-	($f:ident, $name:literal =~ $($tt:tt)+) => (
-		$crate::whr_comp!($f, $name, =~, $($tt)*);
+	// in sub query, needs to be before in
+	(
+		$f:ident, [$name:expr, $path:expr]
+		IN (SELECT $sel:literal FROM $table:literal $($inner:tt)*)
+		$($tt:tt)*
+	) => (
+		$crate::filter_sub_query!(
+			$f, [IN $name, $path, $sel], $table, [$($inner)*]
+		);
+		$crate::whr_log!($f, $($tt)*);
 	);
 	// in
-	($f:ident, $name:literal IN $($tt:tt)+) => (
-		$crate::whr_comp_in!($f, $name, $($tt)*);
+	($f:ident, $col:tt IN $($tt:tt)+) => (
+		$crate::whr_comp_in!($f, $col, $($tt)*);
+	);
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! filter_json {
+	(start; $f:ident, $name:literal, $($tt:tt)+) => (
+		{
+			let path = $crate::filter::JsonPath::new();
+			$crate::filter_json!($f, path, $name, $($tt)+);
+		}
+	);
+
+	($f:ident, $path:ident, $name:literal, ->> $key:literal $($tt:tt)+) => (
+		let $path = $path.get_text($key);
+		$crate::filter_json!($f, $path, $name, $($tt)+);
+	);
+	($f:ident, $path:ident, $name:literal, -> $key:literal $($tt:tt)+) => (
+		let $path = $path.get($key);
+		$crate::filter_json!($f, $path, $name, $($tt)+);
+	);
+	(
+		$f:ident, $path:ident, $name:literal,
+		#>> [$($key:literal),*] $($tt:tt)+
+	) => (
+		let $path = $path.path_text([$($key),*]);
+		$crate::filter_json!($f, $path, $name, $($tt)+);
+	);
+	(
+		$f:ident, $path:ident, $name:literal,
+		#> [$($key:literal),*] $($tt:tt)+
+	) => (
+		let $path = $path.path([$($key),*]);
+		$crate::filter_json!($f, $path, $name, $($tt)+);
+	);
+	($f:ident, $path:ident, $name:literal, $($tt:tt)+) => (
+		$crate::filter_op!($f, [$name, Some($path)] $($tt)+);
+	);
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! filter_group {
+	($f:ident, $kind:ident, $($tt:tt)*) => (
+		{
+			#[allow(unused_mut)]
+			let mut g = $crate::filter::WhereFilter::new();
+			$crate::filter_inner!(g, $($tt)*);
+
+			$f.whr.push($crate::filter::WherePart::$kind(g.whr));
+			$f.params.append(g.params);
+		}
+	);
+}
+
+/// The params of the sub query are merged into the outer ones
+#[doc(hidden)]
+#[macro_export]
+macro_rules! filter_sub_query {
+	($f:ident, $kind:tt, $table:literal, [WHERE $($inner:tt)+]) => (
+		{
+			#[allow(unused_mut)]
+			let mut g = $crate::filter::WhereFilter::new();
+			$crate::filter_inner!(g, $($inner)+);
+
+			$crate::filter_sub_query!(push; $f, $kind, $table, g);
+		}
+	);
+	($f:ident, $kind:tt, $table:literal, []) => (
+		{
+			let g = $crate::filter::WhereFilter::new();
+			$crate::filter_sub_query!(push; $f, $kind, $table, g);
+		}
+	);
+
+	(
+		push; $f:ident, [IN $name:expr, $path:expr, $sel:literal],
+		$table:literal, $g:ident
+	) => (
+		let query = $crate::filter::SubQuery::new(
+			$table,
+			::std::option::Option::Some($crate::Ident::new($sel)),
+			$g.whr,
+		);
+		$f.whr.push($crate::filter::WhereOperation {
+			kind: $crate::filter::Operator::Any,
+			column: ::std::convert::Into::into($name),
+			path: $path,
+			value: $crate::filter::WhereIdent::SubQuery(
+				::std::boxed::Box::new(query)
+			),
+		});
+		$f.params.append($g.params);
+	);
+	(push; $f:ident, [EXISTS], $table:literal, $g:ident) => (
+		let query = $crate::filter::SubQuery::new(
+			$table,
+			::std::option::Option::None,
+			$g.whr,
+		);
+		$f.whr.push($crate::filter::WherePart::Exists(query));
+		$f.params.append($g.params);
 	);
 }
 
 #[doc(hidden)]
 #[macro_export]
 macro_rules! whr_comp {
-	($f:ident, $name:expr, $symb:tt, &$value:tt $($tt:tt)*) => (
-		$crate::whr_comp!(symb; $f, $name, $symb, &$value, $($tt)*);
+	// sql expression, needs to be before value
+	($f:ident, $col:tt, $symb:ident, SQL($sql:literal) $($tt:tt)*) => (
+		$crate::whr_comp!(
+			ident; $f, $col, $symb,
+			$crate::filter::WhereIdent::Sql(
+				::std::borrow::Cow::Borrowed($sql)
+			),
+			$($tt)*
+		);
+	);
+	// another column qualified with a table or alias
+	(
+		$f:ident, $col:tt, $symb:ident,
+		$table:literal . $other:literal $($tt:tt)*
+	) => (
+		$crate::whr_comp!(
+			ident; $f, $col, $symb,
+			$crate::filter::WhereIdent::Name(
+				$crate::Ident::qualified($table, $other)
+			),
+			$($tt)*
+		);
+	);
+	// another column
+	($f:ident, $col:tt, $symb:ident, $other:literal $($tt:tt)*) => (
+		$crate::whr_comp!(
+			ident; $f, $col, $symb,
+			$crate::filter::WhereIdent::Name(
+				$crate::Ident::new($other)
+			),
+			$($tt)*
+		);
 	);
-	($f:ident, $name:expr, $symb:tt, $value:tt $($tt:tt)*) => (
-		$crate::whr_comp!(symb; $f, $name, $symb, $value, $($tt)*);
+	($f:ident, $col:tt, $symb:tt, &$value:tt $($tt:tt)*) => (
+		$crate::whr_comp!(symb; $f, $col, $symb, &$value, $($tt)*);
+	);
+	($f:ident, $col:tt, $symb:tt, $value:tt $($tt:tt)*) => (
+		$crate::whr_comp!(symb; $f, $col, $symb, $value, $($tt)*);
 	);
 
-	(symb; $f:ident, $name:expr, ~, $value:expr, $($tt:tt)*) => (
-		let param = $crate::filter::Param::new_owned($name, format!("%{}%", $value));
-		$crate::whr_comp!(fin; $f, param, Like, $($tt)*);
+	(symb; $f:ident, [$name:expr, $path:expr], [~], $value:expr, $($tt:tt)*) => (
+		let value = $crate::filter::escape_like(&$value.to_string());
+		let param = $crate::filter::Param::new_owned($name, format!("%{}%", value));
+		$crate::whr_comp!(
+			fin; $f, param, $path, $crate::filter::Operator::Like, $($tt)*
+		);
 	);
-	(symb; $f:ident, $name:expr, ~=, $value:expr, $($tt:tt)*) => (
-		let param = $crate::filter::Param::new_owned($name, format!("%{}", $value));
-		$crate::whr_comp!(fin; $f, param, Like, $($tt)*);
+	(symb; $f:ident, [$name:expr, $path:expr], [~=], $value:expr, $($tt:tt)*) => (
+		let value = $crate::filter::escape_like(&$value.to_string());
+		let param = $crate::filter::Param::new_owned($name, format!("%{}", value));
+		$crate::whr_comp!(
+			fin; $f, param, $path, $crate::filter::Operator::Like, $($tt)*
+		);
 	);
-	(symb; $f:ident, $name:expr, =~, $value:expr, $($tt:tt)*) => (
-		let param = $crate::filter::Param::new_owned($name, format!("{}%", $value));
-		$crate::whr_comp!(fin; $f, param, Like, $($tt)*);
+	(symb; $f:ident, [$name:expr, $path:expr], [=~], $value:expr, $($tt:tt)*) => (
+		let value = $crate::filter::escape_like(&$value.to_string());
+		let param = $crate::filter::Param::new_owned($name, format!("{}%", value));
+		$crate::whr_comp!(
+			fin; $f, param, $path, $crate::filter::Operator::Like, $($tt)*
+		);
 	);
-	(symb; $f:ident, $name:expr, $symb:ident, $value:expr, $($tt:tt)*) => (
+	(symb; $f:ident, [$name:expr, $path:expr], [@@ $search:expr], $value:expr, $($tt:tt)*) => (
 		let param = $crate::filter::Param::new($name, $value);
-		$crate::whr_comp!(fin; $f, param, $symb, $($tt)*);
-	);
-	(fin; $f:ident, $param:expr, $symb:ident, $($tt:tt)*) => (
-		let symb = $crate::filter::Operator::$symb;
-
-		let mut cont = true;
-
-		// todo: can this become a noop
-		if $param.is_null() {
-			match symb {
-				$crate::filter::Operator::Eq => {
-					$f.whr.push($crate::filter::WhereOperation {
-						kind: $crate::filter::Operator::IsNull,
-						column: $param.name.into()
-					});
-					cont = false;
-				},
-				$crate::filter::Operator::Ne => {
-					$f.whr.push($crate::filter::WhereOperation {
-						kind: $crate::filter::Operator::IsNotNull,
-						column: $param.name.into()
-					});
-					cont = false;
-				},
-				_ => {}
+		$crate::whr_comp!(
+			fin; $f, param, $path,
+			$crate::filter::Operator::TextSearch($search), $($tt)*
+		);
+	);
+	// the elements of the list need the type of the column
+	(symb; $f:ident, [$name:expr, $path:expr], Any, $value:expr, $($tt:tt)*) => (
+		let param = $crate::filter::Param::new_list($name, $value);
+		$crate::whr_comp!(
+			fin; $f, param, $path, $crate::filter::Operator::Any, $($tt)*
+		);
+	);
+	(symb; $f:ident, [$name:expr, $path:expr], NeAll, $value:expr, $($tt:tt)*) => (
+		let param = $crate::filter::Param::new_list($name, $value);
+		$crate::whr_comp!(
+			fin; $f, param, $path, $crate::filter::Operator::NeAll, $($tt)*
+		);
+	);
+	(symb; $f:ident, [$name:expr, $path:expr], $symb:ident, $value:expr, $($tt:tt)*) => (
+		let param = $crate::filter::Param::new($name, $value);
+		$crate::whr_comp!(
+			fin; $f, param, $path, $crate::filter::Operator::$symb, $($tt)*
+		);
+	);
+	(
+		ident; $f:ident, [$name:expr, $path:expr], $symb:ident,
+		$value:expr, $($tt:tt)*
+	) => (
+		$f.whr.push($crate::filter::WhereOperation {
+			kind: $crate::filter::Operator::$symb,
+			column: ::std::convert::Into::into($name),
+			path: $path,
+			value: $value,
+		});
+
+		$crate::whr_log!($f, $($tt)*);
+	);
+	(fin; $f:ident, $param:expr, $path:expr, $kind:expr, $($tt:tt)*) => (
+		// comparing with null needs IS NULL
+		let kind = match $kind {
+			$crate::filter::Operator::Eq if $param.is_null() => {
+				$crate::filter::Operator::IsNull
 			}
-		}
+			$crate::filter::Operator::Ne if $param.is_null() => {
+				$crate::filter::Operator::IsNotNull
+			}
+			kind => kind,
+		};
+		let has_param = !matches!(
+			kind,
+			$crate::filter::Operator::IsNull
+				| $crate::filter::Operator::IsNotNull
+		);
 
-		if cont {
-			$f.whr.push($crate::filter::WhereOperation {
-				kind: symb,
-				column: $param.name.into()
-			});
+		$f.whr.push($crate::filter::WhereOperation {
+			kind,
+			column: $param.name.clone(),
+			path: $path,
+			value: $crate::filter::WhereIdent::Param,
+		});
+		if has_param {
 			$f.params.push($param);
 		}
 
 		$crate::whr_log!($f, $($tt)*);
 	);
-	// LIMIT
 }
 
 #[doc(hidden)]
 #[macro_export]
 macro_rules! whr_comp_in {
-	($f:ident, $name:expr, &$value:tt $($tt:tt)*) => (
-		$crate::whr_comp_in!(two; $f, $name, &$value, $($tt)*);
+	($f:ident, $col:tt, &$value:tt $($tt:tt)*) => (
+		$crate::whr_comp_in!(two; $f, $col, &$value, $($tt)*);
 	);
-	($f:ident, $name:expr, $value:tt $($tt:tt)*) => (
-		$crate::whr_comp_in!(two; $f, $name, $value, $($tt)*);
+	($f:ident, $col:tt, $value:tt $($tt:tt)*) => (
+		$crate::whr_comp_in!(two; $f, $col, $value, $($tt)*);
 	);
 
-	(two; $f:ident, $name:expr, $value:expr, $($tt:tt)*) => (
-		{
-			let mut c = 0;
-			for val in $value {
-				c += 1;
-				let param = $crate::filter::Param::new($name, val);
-				$f.params.push(param);
-			}
-
-			$f.whr.push($crate::filter::WhereOperation {
-				kind: $crate::filter::Operator::In { length: c },
-				column: $name.into()
-			});
-		}
-
-		$crate::whr_log!($f, $($tt)*);
+	// the values are bound as one array param, `= ANY($1)`
+	(two; $f:ident, [$name:expr, $path:expr], $value:expr, $($tt:tt)*) => (
+		let values = $value;
+		let param = $crate::filter::Param::new_owned(
+			$crate::filter::list_column($name, &values),
+			::std::iter::IntoIterator::into_iter(values)
+				.collect::<::std::vec::Vec<_>>()
+		);
+		$crate::whr_comp!(
+			fin; $f, param, $path, $crate::filter::Operator::Any, $($tt)*
+		);
 	);
 }
 
@@ -247,14 +651,137 @@ macro_rules! whr_log {
 #[doc(hidden)]
 #[macro_export]
 macro_rules! filter_order {
-	($f:ident, $name:literal DESC $($tt:tt)*) => (
-		$f.order_by.push_desc($name);
+	// nulls first or last, needs to be before all others
+	(nulls; $f:ident, $part:expr, NULLS FIRST $($tt:tt)*) => (
+		$f.order_by.push($part.nulls_first());
 		$crate::filter_order!($f, $($tt)*);
 	);
-	($f:ident, $name:literal ASC $($tt:tt)*) => (
-		$f.order_by.push_asc($name);
+	(nulls; $f:ident, $part:expr, NULLS LAST $($tt:tt)*) => (
+		$f.order_by.push($part.nulls_last());
 		$crate::filter_order!($f, $($tt)*);
 	);
+	(nulls; $f:ident, $part:expr, $($tt:tt)*) => (
+		$f.order_by.push($part);
+		$crate::filter_order!($f, $($tt)*);
+	);
+
+	($f:ident, $name:literal DESC $($tt:tt)*) => (
+		$crate::filter_order!(
+			nulls; $f, $crate::filter::OrderByPart::desc($name), $($tt)*
+		);
+	);
+	($f:ident, $name:literal ASC $($tt:tt)*) => (
+		$crate::filter_order!(
+			nulls; $f, $crate::filter::OrderByPart::asc($name), $($tt)*
+		);
+	);
+	// column qualified with a table or alias
+	($f:ident, $table:literal . $name:literal DESC $($tt:tt)*) => (
+		$crate::filter_order!(
+			nulls; $f,
+			$crate::filter::OrderByPart::desc(
+				$crate::Ident::qualified($table, $name)
+			),
+			$($tt)*
+		);
+	);
+	($f:ident, $table:literal . $name:literal ASC $($tt:tt)*) => (
+		$crate::filter_order!(
+			nulls; $f,
+			$crate::filter::OrderByPart::asc(
+				$crate::Ident::qualified($table, $name)
+			),
+			$($tt)*
+		);
+	);
+	// typed column
+	($f:ident, $ty:ident :: $col:ident DESC $($tt:tt)*) => (
+		$crate::filter_order!(
+			nulls; $f, $crate::filter::OrderByPart::desc($ty::$col), $($tt)*
+		);
+	);
+	($f:ident, $ty:ident :: $col:ident ASC $($tt:tt)*) => (
+		$crate::filter_order!(
+			nulls; $f, $crate::filter::OrderByPart::asc($ty::$col), $($tt)*
+		);
+	);
+	// sql expression
+	($f:ident, SQL($sql:literal) DESC $($tt:tt)*) => (
+		$crate::filter_order!(
+			nulls; $f,
+			$crate::filter::OrderByPart::sql(
+				$sql, $crate::filter::Direction::Desc
+			),
+			$($tt)*
+		);
+	);
+	($f:ident, SQL($sql:literal) ASC $($tt:tt)*) => (
+		$crate::filter_order!(
+			nulls; $f,
+			$crate::filter::OrderByPart::sql(
+				$sql, $crate::filter::Direction::Asc
+			),
+			$($tt)*
+		);
+	);
+	// a direction variable, needs to be after ASC and DESC
+	($f:ident, $name:literal $dir:ident $($tt:tt)*) => (
+		$crate::filter_order!(
+			nulls; $f, $crate::filter::OrderByPart::column($name, $dir),
+			$($tt)*
+		);
+	);
+	($f:ident, $table:literal . $name:literal $dir:ident $($tt:tt)*) => (
+		$crate::filter_order!(
+			nulls; $f,
+			$crate::filter::OrderByPart::column(
+				$crate::Ident::qualified($table, $name),
+				$dir
+			),
+			$($tt)*
+		);
+	);
+	($f:ident, $ty:ident :: $col:ident $dir:ident $($tt:tt)*) => (
+		$crate::filter_order!(
+			nulls; $f, $crate::filter::OrderByPart::column($ty::$col, $dir),
+			$($tt)*
+		);
+	);
+	($f:ident, SQL($sql:literal) $dir:ident $($tt:tt)*) => (
+		$crate::filter_order!(
+			nulls; $f, $crate::filter::OrderByPart::sql($sql, $dir), $($tt)*
+		);
+	);
+	($f:ident, $ty:ident :: $col:ident <-> $($tt:tt)+) => (
+		$crate::filter_order_param!($f, $ty::$col, push_distance(), $($tt)+);
+	);
+	($f:ident, $name:literal <-> $($tt:tt)+) => (
+		$crate::filter_order_param!($f, $name, push_distance(), $($tt)+);
+	);
+	($f:ident, $name:literal @@ PLAIN ($lang:literal) $($tt:tt)+) => (
+		$crate::filter_order_param!(
+			$f, $name,
+			push_rank($crate::filter::TextSearch::plain().language($lang)),
+			$($tt)+
+		);
+	);
+	($f:ident, $name:literal @@ PLAIN $($tt:tt)+) => (
+		$crate::filter_order_param!(
+			$f, $name, push_rank($crate::filter::TextSearch::plain()), $($tt)+
+		);
+	);
+	($f:ident, $name:literal @@ ($lang:literal) $($tt:tt)+) => (
+		$crate::filter_order_param!(
+			$f, $name,
+			push_rank($crate::filter::TextSearch::websearch().language($lang)),
+			$($tt)+
+		);
+	);
+	($f:ident, $name:literal @@ $($tt:tt)+) => (
+		$crate::filter_order_param!(
+			$f, $name, push_rank($crate::filter::TextSearch::websearch()), $($tt)+
+		);
+	);
 	($f:ident, LIMIT $($tt:tt)+) => (
 		$crate::filter_limit!($f, $($tt)+);
 	);
@@ -264,6 +791,39 @@ macro_rules! filter_order {
 	($f:ident,) => ();
 }
 
+/// An order by part which needs a param, `$push` is the method on `OrderBy`
+#[doc(hidden)]
+#[macro_export]
+macro_rules! filter_order_param {
+	(
+		$f:ident, $name:expr, $push:ident($($arg:expr),*),
+		&$value:tt $($tt:tt)*
+	) => (
+		$crate::filter_order_param!(
+			val; $f, $name, $push($($arg),*), &$value, $($tt)*
+		);
+	);
+	(
+		$f:ident, $name:expr, $push:ident($($arg:expr),*),
+		$value:tt $($tt:tt)*
+	) => (
+		$crate::filter_order_param!(
+			val; $f, $name, $push($($arg),*), $value, $($tt)*
+		);
+	);
+
+	(
+		val; $f:ident, $name:expr, $push:ident($($arg:expr),*),
+		$value:expr, $($tt:tt)*
+	) => (
+		let param = $crate::filter::Param::new($name, $value);
+		$f.order_by.$push($name $(, $arg)*);
+		$f.params.push(param);
+
+		$crate::filter_order!($f, $($tt)*);
+	);
+}
+
 #[doc(hidden)]
 #[macro_export]
 macro_rules! filter_limit {
@@ -321,6 +881,7 @@ macro_rules! filter_offset {
 
 #[cfg(test)]
 mod tests {
+	use crate::filter::Direction;
 	use crate::UniqueId;
 
 	#[test]
@@ -336,6 +897,147 @@ mod tests {
 		let id = "str";
 		let query = filter!("id" ~ id);
 		assert_eq!(query.to_string(), r#" WHERE "id" LIKE $1"#);
+		let query = filter!("id" ~= id AND "name" =~ id);
+		assert_eq!(
+			query.to_string(),
+			r#" WHERE "id" LIKE $1 AND "name" LIKE $2"#
+		);
+	}
+
+	#[test]
+	fn test_group() {
+		let a = 1;
+		let b = 2;
+		let c = 3;
+		let query = filter!(&a AND ("b" = &b OR "c" > &c) OR NOT (&c) LIMIT &a);
+		assert_eq!(
+			query.to_string(),
+			r#" WHERE "a" = $1 AND ("b" = $2 OR "c" > $3) OR NOT ("c" = $4) LIMIT $5"#
+		);
+		assert_eq!(query.params.len(), 5);
+
+		let query = whr!((&a AND NOT (&b OR (&c))));
+		assert_eq!(
+			query.to_string(),
+			r#" WHERE ("a" = $1 AND NOT ("b" = $2 OR ("c" = $3)))"#
+		);
+	}
+
+	#[test]
+	fn test_match_operators() {
+		let a = "a";
+		let query = filter!(
+			"a" ILIKE &a AND "b" NOT ILIKE &a OR "c" SIMILAR TO &a
+			AND "d" REGEX &a AND "e" ~* &a AND "f" !~ &a AND "g" !~* &a
+		);
+		assert_eq!(
+			query.to_string(),
+			r#" WHERE "a" ILIKE $1 AND "b" NOT ILIKE $2 OR "c" SIMILAR TO $3 AND "d" ~ $4 AND "e" ~* $5 AND "f" !~ $6 AND "g" !~* $7"#
+		);
+	}
+
+	#[test]
+	fn test_array_operators() {
+		let ids = vec![1, 2];
+		let tags = vec!["a".to_string()];
+		let query = filter!(
+			"id" = ANY &ids AND "id" != ALL &ids OR "tags" @> &tags
+			AND "tags" <@ &tags AND "tags" && &tags
+		);
+		assert_eq!(
+			query.to_string(),
+			r#" WHERE "id" = ANY($1) AND "id" != ALL($2) OR "tags" @> $3 AND "tags" <@ $4 AND "tags" && $5"#
+		);
+	}
+
+	#[test]
+	fn test_in() {
+		let ids = [1, 2, 3];
+		let other = vec!["a".to_string()];
+		let query = filter!("id" IN &ids AND "name" IN &other);
+		assert_eq!(
+			query.to_string(),
+			r#" WHERE "id" = ANY($1) AND "name" = ANY($2)"#
+		);
+		assert_eq!(query.params.len(), 2);
+	}
+
+	#[test]
+	fn test_json() {
+		let plan = "pro";
+		let key = "beta";
+		let query = filter!(
+			"meta"->"plan"->>"name" = &plan AND "meta"#>>["a", "it's"] ~ plan
+			OR "meta"->0 ? &key AND "meta" ? &key AND "id" = &plan
+		);
+		assert_eq!(
+			query.to_string(),
+			r##" WHERE "meta"->'plan'->>'name' = $1 AND "meta"#>>ARRAY['a','it''s'] LIKE $2 OR "meta"->0 ? $3 AND "meta" ? $4 AND "id" = $5"##
+		);
+	}
+
+	#[test]
+	fn test_search() {
+		let q = "rust -java";
+		let r = &q;
+		let query = filter!(
+			"search" @@ &q AND "title" @@ ("english") &q
+			OR "body" @@ PLAIN r AND "body" @@ PLAIN ("german") r
+			ORDER "search" @@ ("english") &q "created" DESC LIMIT 10
+		);
+		assert_eq!(
+			query.to_string(),
+			r#" WHERE "search" @@ websearch_to_tsquery($1) AND "title" @@ websearch_to_tsquery('english', $2) OR "body" @@ plainto_tsquery($3) AND "body" @@ plainto_tsquery('german', $4) ORDER BY ts_rank("search", websearch_to_tsquery('english', $5)) DESC, "created" DESC LIMIT 10"#
+		);
+		assert_eq!(query.params.len(), 5);
+	}
+
+	#[test]
+	fn test_trigram() {
+		let name = "jon";
+		let query = filter!(
+			"name" % &name OR "name" %> &name
+			OR SIMILARITY("name", &name) > 0.3
+			AND WORD_SIMILARITY("title", &name) > 0.5
+			ORDER "name" <-> &name LIMIT 5
+		);
+		assert_eq!(
+			query.to_string(),
+			r#" WHERE "name" % $1 OR "name" %> $2 OR similarity("name", $3) > 0.3 AND word_similarity($4, "title") > 0.5 ORDER BY "name" <-> $5 ASC LIMIT 5"#
+		);
+		assert_eq!(query.params.len(), 5);
+	}
+
+	#[test]
+	fn test_column_and_sql() {
+		let a = 1;
+		let query = filter!(
+			"updated" > "created" AND "a" = &a
+			OR "expires" <= SQL("now()") AND ("day" = SQL("CURRENT_DATE"))
+		);
+		assert_eq!(
+			query.to_string(),
+			r#" WHERE "updated" > "created" AND "a" = $1 OR "expires" <= now() AND ("day" = CURRENT_DATE)"#
+		);
+		assert_eq!(query.params.len(), 1);
+	}
+
+	#[test]
+	fn test_sub_query() {
+		let a = 1;
+		let active = true;
+		let query = filter!(
+			"a" = &a
+			AND "user_id" IN (SELECT "id" FROM "users" WHERE "active" = &active)
+			AND NOT EXISTS (SELECT 1 FROM "bans" WHERE "user_id" = SQL("\"posts\".\"user_id\"") AND "a" > &a)
+			OR EXISTS (SELECT 1 FROM "admins")
+			LIMIT 10
+		);
+		assert_eq!(
+			query.to_string(),
+			r#" WHERE "a" = $1 AND "user_id" = ANY(SELECT "id" FROM "users" WHERE "active" = $2) AND NOT (EXISTS (SELECT 1 FROM "bans" WHERE "user_id" = "posts"."user_id" AND "a" > $3)) OR EXISTS (SELECT 1 FROM "admins") LIMIT 10"#
+		);
+		assert_eq!(query.params.len(), 3);
 	}
 
 	#[test]
@@ -346,16 +1048,18 @@ mod tests {
 		assert_eq!(query.to_string(), " WHERE \"id\" = $1 LIMIT $2");
 	}
 
-	// #[test]
-	// fn test_order() {
-	// 	let id = &UniqueId::new();
-	// 	let limit = 10;
-	// 	let query = whr!(id ORDER "id" ASC LIMIT limit);
-	// 	assert_eq!(
-	// 		query.sql.to_string().trim(),
-	// 		"\"id\" = $1 ORDER BY \"id\" ASC  LIMIT 10"
-	// 	);
-	// 	let query = whr!(ORDER "id" DESC);
-	// 	assert_eq!(query.sql.to_string().trim(), "ORDER BY \"id\" DESC");
-	// }
+	#[test]
+	fn test_order() {
+		let id = &UniqueId::new();
+		let dir: Direction = "desc".parse().unwrap();
+		let query = filter!(
+			id ORDER "created" DESC NULLS LAST "name" dir
+			SQL("lower(\"email\")") ASC NULLS FIRST "id" ASC LIMIT 10
+		);
+		assert_eq!(
+			query.to_string(),
+			r#" WHERE "id" = $1 ORDER BY "created" DESC NULLS LAST, "name" DESC, lower("email") ASC NULLS FIRST, "id" ASC LIMIT 10"#
+		);
+		assert_eq!(query.params.len(), 1);
+	}
 }
@@ -0,0 +1,139 @@
+use std::borrow::Cow;
+use std::fmt::{self, Write};
+
+/// The name of a table or a column.
+///
+/// An identifier is always written to sql inside double quotes, quotes in
+/// the name are escaped, so a name which comes from user input cannot break
+/// out of the quoting. Names which are known at compile time can be created
+/// with the `const` constructor [`Ident::new`].
+///
+/// A column can be qualified with a table or its alias, which is needed when
+/// several tables are joined, see [`Join`](crate::filter::Join).
+///
+/// ## Example
+/// ```
+/// use fire_postgres::Ident;
+///
+/// const USERS: Ident = Ident::new("users");
+/// assert_eq!(USERS.to_string(), r#""users""#);
+///
+/// let name = String::from(r#"name"; DROP TABLE "users"#);
+/// assert_eq!(
+/// 	Ident::from(name).to_string(),
+/// 	r#""name""; DROP TABLE ""users""#
+/// );
+///
+/// let name = Ident::qualified("u", "name");
+/// assert_eq!(name.to_string(), r#""u"."name""#);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Ident {
+	table: Option<Cow<'static, str>>,
+	inner: Cow<'static, str>,
+}
+
+impl Ident {
+	pub const fn new(name: &'static str) -> Self {
+		Self {
+			table: None,
+			inner: Cow::Borrowed(name),
+		}
+	}
+
+	/// A column of a table or an alias, written as `"table"."name"`.
+	pub const fn qualified(table: &'static str, name: &'static str) -> Self {
+		Self {
+			table: Some(Cow::Borrowed(table)),
+			inner: Cow::Borrowed(name),
+		}
+	}
+
+	/// Qualifies the column with a table or an alias.
+	pub fn with_table(mut self, table: impl Into<Cow<'static, str>>) -> Self {
+		self.table = Some(table.into());
+		self
+	}
+
+	/// Returns the table or alias the column is qualified with.
+	pub fn table(&self) -> Option<&str> {
+		self.table.as_deref()
+	}
+
+	/// Returns the name without quotes and without the table.
+	pub fn as_str(&self) -> &str {
+		&self.inner
+	}
+}
+
+impl From<&'static str> for Ident {
+	fn from(name: &'static str) -> Self {
+		Self::new(name)
+	}
+}
+
+impl From<String> for Ident {
+	fn from(name: String) -> Self {
+		Self {
+			table: None,
+			inner: Cow::Owned(name),
+		}
+	}
+}
+
+impl From<Cow<'static, str>> for Ident {
+	fn from(name: Cow<'static, str>) -> Self {
+		Self {
+			table: None,
+			inner: name,
+		}
+	}
+}
+
+impl From<&Ident> for Ident {
+	fn from(ident: &Ident) -> Self {
+		ident.clone()
+	}
+}
+
+impl AsRef<str> for Ident {
+	fn as_ref(&self) -> &str {
+		self.as_str()
+	}
+}
+
+impl PartialEq<str> for Ident {
+	fn eq(&self, other: &str) -> bool {
+		self.as_str() == other
+	}
+}
+
+impl PartialEq<&str> for Ident {
+	fn eq(&self, other: &&str) -> bool {
+		self.as_str() == *other
+	}
+}
+
+/// Writes the name quoted and escaped.
+impl fmt::Display for Ident {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if let Some(table) = &self.table {
+			write_quoted(f, table)?;
+			f.write_char('.')?;
+		}
+
+		write_quoted(f, self.as_str())
+	}
+}
+
+/// Writes `"name"` and doubles every quote in the name.
+pub(crate) fn write_quoted(w: &mut impl fmt::Write, name: &str) -> fmt::Result {
+	w.write_char('"')?;
+	for (i, part) in name.split('"').enumerate() {
+		if i != 0 {
+			w.write_str("\"\"")?;
+		}
+		w.write_str(part)?;
+	}
+	w.write_char('"')
+}
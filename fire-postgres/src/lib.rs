@@ -1,6 +1,7 @@
 #![allow(clippy::tabs_in_doc_comments)]
 #![allow(clippy::never_loop)]
 #![allow(clippy::new_without_default)]
+#![allow(clippy::module_inception)]
 
 pub mod database;
 pub use database::Database;
@@ -12,6 +13,9 @@ pub use connection::Error;
 pub mod row;
 pub use row::Row;
 
+mod ident;
+pub use ident::Ident;
+
 pub mod table;
 // pub use table::Table;
 
@@ -24,7 +28,24 @@ pub mod filter;
 
 pub mod migrations;
 
-pub use fire_postgres_derive::{row, FromRow, TableTempl, ToRow};
+#[cfg(feature = "testing")]
+pub mod memory;
+
+#[cfg(feature = "testing")]
+pub mod mock;
+
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[cfg(all(test, feature = "testing"))]
+mod test_util;
+
+pub use fire_postgres_derive::{
+	row, FromJoinedRow, FromRow, TableTempl, ToRow,
+};
+
+#[cfg(feature = "testing")]
+pub use fire_postgres_derive::test;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
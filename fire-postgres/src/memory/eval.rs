@@ -0,0 +1,360 @@
+use std::cmp::Ordering;
+
+use super::value::{like, Value};
+
+use crate::filter::{
+	Direction, Filter, Keyset, Limit, Offset, Operator, OrderExpr, Where,
+	WhereIdent, WherePart,
+};
+use crate::row::MemoryRow;
+use crate::Error;
+
+/// Returns the value of a column, columns which don't exist are null.
+pub fn column_value(row: &MemoryRow, column: &str) -> Result<Value, Error> {
+	match row.names.iter().position(|n| n == column) {
+		Some(i) => row.values[i].decode().map_err(Error::Deserialize),
+		None => Ok(Value::Null),
+	}
+}
+
+/// Checks if a row matches the where clause.
+///
+/// The params need to be in the same order as they are in the filter.
+pub fn matches(
+	whr: &Where,
+	params: &[Value],
+	row: &MemoryRow,
+) -> Result<bool, Error> {
+	if whr.is_empty() {
+		return Ok(true);
+	}
+
+	let mut param_num = 0;
+	eval_where(whr, params, &mut param_num, row).map(|r| r == Some(true))
+}
+
+/// Evaluates a where clause with sql's three valued logic, `None` is unknown.
+fn eval_where(
+	whr: &Where,
+	params: &[Value],
+	param_num: &mut usize,
+	row: &MemoryRow,
+) -> Result<Option<bool>, Error> {
+	if whr.is_empty() {
+		return Ok(Some(true));
+	}
+
+	// AND binds stronger than OR, so each entry is a chain of ANDs
+	let mut ors: Vec<Option<bool>> = vec![Some(true)];
+
+	for part in whr.parts() {
+		let res = match part {
+			WherePart::And => continue,
+			WherePart::Or => {
+				ors.push(Some(true));
+				continue;
+			}
+			WherePart::Nested(whr) => eval_where(whr, params, param_num, row)?,
+			WherePart::Not(whr) => {
+				eval_where(whr, params, param_num, row)?.map(|r| !r)
+			}
+			WherePart::Operation(op) => {
+				if op.path.is_some() {
+					return Err(unsupported("json paths"));
+				}
+
+				let column = column_value(row, op.column.as_str())?;
+				match &op.value {
+					WhereIdent::Param => {
+						operation(&op.kind, &column, params, param_num)?
+					}
+					WhereIdent::Name(other) => {
+						let other = column_value(row, other.as_str())?;
+						operation(&op.kind, &column, &[other], &mut 0)?
+					}
+					WhereIdent::Sql(_) => {
+						return Err(unsupported("sql expressions"));
+					}
+					WhereIdent::SubQuery(_) => {
+						return Err(unsupported("sub queries"));
+					}
+				}
+			}
+			WherePart::Raw { .. } => return Err(unsupported("raw sql")),
+			WherePart::Exists(_) => return Err(unsupported("sub queries")),
+			WherePart::Keyset(keyset) => {
+				eval_keyset(keyset, params, param_num, row)?
+			}
+		};
+
+		let last = ors.last_mut().unwrap();
+		*last = and(*last, res);
+	}
+
+	Ok(ors.into_iter().fold(Some(false), or))
+}
+
+/// The row matches if the first column which is not equal to the cursor
+/// value comes after it.
+fn eval_keyset(
+	keyset: &Keyset,
+	params: &[Value],
+	param_num: &mut usize,
+	row: &MemoryRow,
+) -> Result<Option<bool>, Error> {
+	let mut res = Some(false);
+	let mut decided = false;
+
+	for col in &keyset.columns {
+		let param = next_param(params, param_num)?;
+		if decided {
+			continue;
+		}
+
+		let ord = column_value(row, col.name.as_str())?.compare(param);
+		let ord = if col.desc {
+			ord.map(Ordering::reverse)
+		} else {
+			ord
+		};
+		match ord {
+			Some(Ordering::Equal) => continue,
+			ord => res = ord.map(Ordering::is_gt),
+		}
+		decided = true;
+	}
+
+	Ok(res)
+}
+
+fn unsupported(what: &str) -> Error {
+	Error::Unknown(
+		format!("{what} is not supported by the memory database").into(),
+	)
+}
+
+fn and(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+	match (a, b) {
+		(Some(false), _) | (_, Some(false)) => Some(false),
+		(Some(true), Some(true)) => Some(true),
+		_ => None,
+	}
+}
+
+fn or(a: Option<bool>, b: Option<bool>) -> Option<bool> {
+	match (a, b) {
+		(Some(true), _) | (_, Some(true)) => Some(true),
+		(Some(false), Some(false)) => Some(false),
+		_ => None,
+	}
+}
+
+fn next_param<'a>(
+	params: &'a [Value],
+	param_num: &mut usize,
+) -> Result<&'a Value, Error> {
+	let param = params.get(*param_num).ok_or_else(|| {
+		Error::Unknown(format!("param ${} missing", *param_num + 1).into())
+	})?;
+	*param_num += 1;
+
+	Ok(param)
+}
+
+fn operation(
+	kind: &Operator,
+	column: &Value,
+	params: &[Value],
+	param_num: &mut usize,
+) -> Result<Option<bool>, Error> {
+	let cmp =
+		|param: &Value, f: fn(Ordering) -> bool| column.compare(param).map(f);
+
+	let res = match kind {
+		Operator::Eq => cmp(next_param(params, param_num)?, Ordering::is_eq),
+		Operator::Ne => cmp(next_param(params, param_num)?, Ordering::is_ne),
+		Operator::Lt => cmp(next_param(params, param_num)?, Ordering::is_lt),
+		Operator::Lte => cmp(next_param(params, param_num)?, Ordering::is_le),
+		Operator::Gt => cmp(next_param(params, param_num)?, Ordering::is_gt),
+		Operator::Gte => cmp(next_param(params, param_num)?, Ordering::is_ge),
+		Operator::Like
+		| Operator::ILike
+		| Operator::NotLike
+		| Operator::NotILike => {
+			let pattern = next_param(params, param_num)?;
+			let (Some(text), Some(pattern)) =
+				(column.as_text(), pattern.as_text())
+			else {
+				return Ok(None);
+			};
+
+			let res = match kind {
+				Operator::ILike | Operator::NotILike => {
+					like(&text.to_lowercase(), &pattern.to_lowercase())
+				}
+				_ => like(text, pattern),
+			};
+
+			Some(res != matches!(kind, Operator::NotLike | Operator::NotILike))
+		}
+		Operator::Regex
+		| Operator::IRegex
+		| Operator::NotRegex
+		| Operator::NotIRegex
+		| Operator::SimilarTo
+		| Operator::HasKey => return Err(unsupported(&format!("{kind:?}"))),
+		Operator::TextSearch(_) => {
+			return Err(unsupported("full text search"));
+		}
+		Operator::Similar
+		| Operator::WordSimilar
+		| Operator::Similarity { .. }
+		| Operator::WordSimilarity { .. } => {
+			return Err(unsupported("trigram similarity"));
+		}
+		Operator::In { length } => {
+			let mut res = Some(false);
+			for _ in 0..*length {
+				let param = next_param(params, param_num)?;
+				res = or(res, column.sql_eq(param));
+			}
+
+			res
+		}
+		Operator::Any | Operator::NeAll => {
+			let Some(array) = next_param(params, param_num)?.as_array() else {
+				return Ok(None);
+			};
+
+			let mut res = Some(matches!(kind, Operator::NeAll));
+			for v in array {
+				res = match kind {
+					Operator::Any => or(res, column.sql_eq(v)),
+					_ => and(res, column.sql_eq(v).map(|eq| !eq)),
+				};
+			}
+
+			res
+		}
+		Operator::Contains | Operator::ContainedBy | Operator::Overlaps => {
+			let param = next_param(params, param_num)?;
+			let (Some(a), Some(b)) = (column.as_array(), param.as_array())
+			else {
+				return Ok(None);
+			};
+
+			let contains = |a: &[Value], b: &[Value]| {
+				b.iter()
+					.all(|b| a.iter().any(|a| a.sql_eq(b) == Some(true)))
+			};
+
+			Some(match kind {
+				Operator::Contains => contains(a, b),
+				Operator::ContainedBy => contains(b, a),
+				_ => a
+					.iter()
+					.any(|a| b.iter().any(|b| a.sql_eq(b) == Some(true))),
+			})
+		}
+		Operator::IsNull => Some(column.is_null()),
+		Operator::IsNotNull => Some(!column.is_null()),
+	};
+
+	Ok(res)
+}
+
+/// Sorts the rows like postgres would, by default nulls are larger than any
+/// other value.
+pub fn sort(
+	filter: &Filter,
+	rows: Vec<MemoryRow>,
+) -> Result<Vec<MemoryRow>, Error> {
+	if filter.order_by.is_empty() {
+		return Ok(rows);
+	}
+
+	let parts = filter.order_by.parts();
+
+	let mut keyed = rows
+		.into_iter()
+		.map(|row| {
+			let key = parts
+				.iter()
+				.map(|part| match &part.expr {
+					OrderExpr::Column(col) => column_value(&row, col.as_str()),
+					OrderExpr::Sql(_) => Err(unsupported("sql expressions")),
+					OrderExpr::Rank { .. } => Err(unsupported("ts_rank")),
+					OrderExpr::Distance(_) => {
+						Err(unsupported("trigram distance"))
+					}
+				})
+				.collect::<Result<Vec<_>, _>>()?;
+
+			Ok((key, row))
+		})
+		.collect::<Result<Vec<_>, Error>>()?;
+
+	keyed.sort_by(|(a, _), (b, _)| {
+		for (part, (a, b)) in parts.iter().zip(a.iter().zip(b)) {
+			let nulls = if part.nulls_are_first() {
+				Ordering::Less
+			} else {
+				Ordering::Greater
+			};
+			let ord = match (a.is_null(), b.is_null()) {
+				(true, true) => Ordering::Equal,
+				(true, false) => nulls,
+				(false, true) => nulls.reverse(),
+				_ => match part.direction {
+					Direction::Asc => a.compare(b).unwrap_or(Ordering::Equal),
+					Direction::Desc => b.compare(a).unwrap_or(Ordering::Equal),
+				},
+			};
+
+			if ord.is_ne() {
+				return ord;
+			}
+		}
+
+		Ordering::Equal
+	});
+
+	Ok(keyed.into_iter().map(|(_, row)| row).collect())
+}
+
+/// Returns the offset and the limit of a filter.
+pub fn offset_limit(
+	filter: &Filter,
+	params: &[Value],
+) -> Result<(usize, Option<usize>), Error> {
+	let param_value = |i: usize| {
+		params
+			.get(i)
+			.and_then(Value::as_int)
+			.and_then(|v| usize::try_from(v).ok())
+			.ok_or_else(|| {
+				Error::Unknown(
+					format!("param ${} is not a number", i + 1).into(),
+				)
+			})
+	};
+
+	let offset_has_param = matches!(filter.offset, Offset::Param);
+
+	let limit = match &filter.limit {
+		Limit::Fixed(v) => Some(*v),
+		Limit::Param => {
+			let i = params.len() - if offset_has_param { 2 } else { 1 };
+			Some(param_value(i)?)
+		}
+		Limit::All => None,
+	};
+
+	let offset = match &filter.offset {
+		Offset::Zero => 0,
+		Offset::Fixed(v) => *v,
+		Offset::Param => param_value(params.len() - 1)?,
+	};
+
+	Ok((offset, limit))
+}
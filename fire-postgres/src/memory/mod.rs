@@ -0,0 +1,688 @@
+//! An in memory backend for tests
+//!
+//! The [`MemoryDatabase`] supports the same table operations as a
+//! [`Connection`](crate::Connection) but keeps all rows in memory. Filters are
+//! evaluated in process, which makes it possible to test code which only
+//! needs simple CRUD semantics without a running postgres server.
+//!
+//! Values are stored in their binary postgres representation, so every type
+//! which can be used as a param can also be stored and read back.
+//!
+//! Requires the `testing` feature.
+//!
+//! ## Example
+//! ```
+//! # // the derives resolve to `crate::` inside of doc tests
+//! # use fire_postgres::{macros, row};
+//! use fire_postgres::memory::MemoryDatabase;
+//! use fire_postgres::{filter, FromRow, ToRow};
+//!
+//! #[derive(Debug, FromRow, ToRow)]
+//! struct User {
+//! 	id: i32,
+//! 	name: String,
+//! }
+//!
+//! # fn main() {
+//! # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+//! let db = MemoryDatabase::new();
+//! db.insert("users", &User { id: 1, name: "Anna".into() }).await.unwrap();
+//!
+//! let name = "Anna";
+//! let user: User = db.select_one("users", filter!(&name)).await.unwrap();
+//! assert_eq!(user.id, 1);
+//! # });
+//! # }
+//! ```
+
+mod eval;
+mod value;
+
+use value::Value;
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use postgres_types::Type;
+
+use crate::connection::no_columns;
+use crate::filter::{
+	keyset_limit, Cursor, Filter, OffsetPage, Page, Params, WhereFilter,
+};
+use crate::row::{
+	parse_columns, FromRowOwned, MemoryRow, NamedColumns, RawValue, ToRow,
+	ToRowStatic,
+};
+use crate::{Error, Ident};
+
+/// A database which holds all tables in memory.
+///
+/// Cloning the database returns a handle to the same tables.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryDatabase {
+	tables: Arc<Mutex<HashMap<String, Vec<MemoryRow>>>>,
+}
+
+impl MemoryDatabase {
+	/// Creates a new empty database
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub async fn select<R>(
+		&self,
+		table: impl Into<Ident>,
+		filter: impl Borrow<Filter<'_>>,
+	) -> Result<Vec<R>, Error>
+	where
+		R: FromRowOwned + NamedColumns,
+	{
+		let filter = filter.borrow();
+		let columns = parse_columns(R::select_columns());
+
+		self.select_rows(table.into().as_str(), filter)?
+			.into_iter()
+			.map(|row| {
+				R::from_row_owned(project(&row, &columns).into())
+					.map_err(Error::Deserialize)
+			})
+			.collect()
+	}
+
+	/// Selects only the given columns, see
+	/// [`Connection::select_columns`](crate::Connection::select_columns).
+	pub async fn select_columns<R, C>(
+		&self,
+		table: impl Into<Ident>,
+		columns: &[C],
+		filter: impl Borrow<Filter<'_>>,
+	) -> Result<Vec<R>, Error>
+	where
+		R: FromRowOwned,
+		C: AsRef<str>,
+	{
+		if columns.is_empty() {
+			return Err(no_columns());
+		}
+
+		let filter = filter.borrow();
+		let columns: Vec<_> =
+			columns.iter().map(|c| c.as_ref().to_string()).collect();
+
+		self.select_rows(table.into().as_str(), filter)?
+			.into_iter()
+			.map(|row| {
+				R::from_row_owned(project(&row, &columns).into())
+					.map_err(Error::Deserialize)
+			})
+			.collect()
+	}
+
+	pub async fn select_one<R>(
+		&self,
+		table: impl Into<Ident>,
+		filter: impl Borrow<Filter<'_>>,
+	) -> Result<R, Error>
+	where
+		R: FromRowOwned + NamedColumns,
+	{
+		self.select_opt(table, filter)
+			.await
+			.and_then(|opt| opt.ok_or(Error::ExpectedOneRow))
+	}
+
+	pub async fn select_opt<R>(
+		&self,
+		table: impl Into<Ident>,
+		filter: impl Borrow<Filter<'_>>,
+	) -> Result<Option<R>, Error>
+	where
+		R: FromRowOwned + NamedColumns,
+	{
+		let filter = filter.borrow();
+		let columns = parse_columns(R::select_columns());
+
+		self.select_rows(table.into().as_str(), filter)?
+			.into_iter()
+			.next()
+			.map(|row| {
+				R::from_row_owned(project(&row, &columns).into())
+					.map_err(Error::Deserialize)
+			})
+			.transpose()
+	}
+
+	/// Selects a page of rows together with the number of all rows which
+	/// match the filter, see
+	/// [`Connection::select_page`](crate::Connection::select_page).
+	pub async fn select_page<R>(
+		&self,
+		table: impl Into<Ident>,
+		filter: impl Borrow<Filter<'_>>,
+		page: usize,
+		per_page: usize,
+	) -> Result<OffsetPage<R>, Error>
+	where
+		R: FromRowOwned + NamedColumns,
+	{
+		let filter = filter.borrow();
+		let params = decode_params(&filter.params)?;
+		let columns = parse_columns(R::select_columns());
+
+		let rows = self.sorted_rows(table.into().as_str(), filter, &params)?;
+		let total = rows.len() as u64;
+
+		let items = rows
+			.iter()
+			.skip(page.saturating_mul(per_page))
+			.take(per_page)
+			.map(|row| {
+				R::from_row_owned(project(row, &columns).into())
+					.map_err(Error::Deserialize)
+			})
+			.collect::<Result<_, _>>()?;
+
+		Ok(OffsetPage {
+			items,
+			total,
+			page,
+			per_page,
+		})
+	}
+
+	/// Selects a page of rows with keyset pagination, see
+	/// [`Connection::select_keyset`](crate::Connection::select_keyset).
+	pub async fn select_keyset<'a, R>(
+		&self,
+		table: impl Into<Ident>,
+		mut filter: Filter<'a>,
+		after: Option<&'a Cursor>,
+	) -> Result<Page<R>, Error>
+	where
+		R: FromRowOwned + NamedColumns,
+	{
+		if let Some(cursor) = after {
+			filter = filter.after(cursor)?;
+		}
+		let limit = keyset_limit(&mut filter)?;
+		let columns = parse_columns(R::select_columns());
+
+		let rows = self
+			.select_rows(table.into().as_str(), &filter)?
+			.iter()
+			.map(|row| project(row, &columns).into())
+			.collect();
+
+		Page::from_rows(rows, &filter.order_by, limit)
+	}
+
+	/// Counts all rows which match the filter and where the column is not
+	/// null.
+	pub async fn count(
+		&self,
+		table: impl Into<Ident>,
+		column: impl Into<Ident>,
+		filter: impl Borrow<Filter<'_>>,
+	) -> Result<u64, Error> {
+		let (table, column) = (table.into(), column.into());
+		let filter = filter.borrow();
+		let params = decode_params(&filter.params)?;
+
+		let tables = self.tables.lock().unwrap();
+		let mut count = 0;
+
+		for row in tables.get(table.as_str()).into_iter().flatten() {
+			if eval::matches(&filter.whr, &params, row)?
+				&& !eval::column_value(row, column.as_str())?.is_null()
+			{
+				count += 1;
+			}
+		}
+
+		Ok(count)
+	}
+
+	pub async fn insert<U>(
+		&self,
+		table: impl Into<Ident>,
+		item: &U,
+	) -> Result<(), Error>
+	where
+		U: ToRow,
+	{
+		let row = to_memory_row(item)?;
+
+		self.tables
+			.lock()
+			.unwrap()
+			.entry(table.into().as_str().to_string())
+			.or_default()
+			.push(row);
+
+		Ok(())
+	}
+
+	pub async fn insert_many<U, I>(
+		&self,
+		table: impl Into<Ident>,
+		items: I,
+	) -> Result<(), Error>
+	where
+		U: ToRowStatic,
+		I: IntoIterator,
+		I::Item: Borrow<U>,
+	{
+		let rows = items
+			.into_iter()
+			.map(|item| to_memory_row(item.borrow()))
+			.collect::<Result<Vec<_>, _>>()?;
+
+		self.tables
+			.lock()
+			.unwrap()
+			.entry(table.into().as_str().to_string())
+			.or_default()
+			.extend(rows);
+
+		Ok(())
+	}
+
+	pub async fn update<U>(
+		&self,
+		table: impl Into<Ident>,
+		item: &U,
+		filter: impl Borrow<WhereFilter<'_>>,
+	) -> Result<(), Error>
+	where
+		U: ToRow,
+	{
+		let filter = filter.borrow();
+		let params = decode_params(&filter.params)?;
+		let update = to_memory_row(item)?;
+
+		let mut tables = self.tables.lock().unwrap();
+
+		for row in tables.get_mut(table.into().as_str()).into_iter().flatten() {
+			if !eval::matches(&filter.whr, &params, row)? {
+				continue;
+			}
+
+			for (name, value) in update.names.iter().zip(&update.values) {
+				match row.names.iter().position(|n| n == name) {
+					Some(i) => row.values[i] = value.clone(),
+					None => {
+						row.names.push(name.clone());
+						row.values.push(value.clone());
+					}
+				}
+			}
+		}
+
+		Ok(())
+	}
+
+	pub async fn delete(
+		&self,
+		table: impl Into<Ident>,
+		filter: impl Borrow<WhereFilter<'_>>,
+	) -> Result<(), Error> {
+		let filter = filter.borrow();
+		let params = decode_params(&filter.params)?;
+
+		let mut tables = self.tables.lock().unwrap();
+		let Some(rows) = tables.get_mut(table.into().as_str()) else {
+			return Ok(());
+		};
+
+		let mut kept = Vec::with_capacity(rows.len());
+		for row in rows.drain(..) {
+			if !eval::matches(&filter.whr, &params, &row)? {
+				kept.push(row);
+			}
+		}
+		*rows = kept;
+
+		Ok(())
+	}
+
+	/// Removes all rows of a table
+	pub fn clear(&self, table: impl Into<Ident>) {
+		self.tables.lock().unwrap().remove(table.into().as_str());
+	}
+
+	fn select_rows(
+		&self,
+		table: &str,
+		filter: &Filter,
+	) -> Result<Vec<MemoryRow>, Error> {
+		let params = decode_params(&filter.params)?;
+		let rows = self.sorted_rows(table, filter, &params)?;
+		let (offset, limit) = eval::offset_limit(filter, &params)?;
+
+		Ok(rows
+			.into_iter()
+			.skip(offset)
+			.take(limit.unwrap_or(usize::MAX))
+			.collect())
+	}
+
+	/// Returns all rows which match the filter in the order of the filter.
+	fn sorted_rows(
+		&self,
+		table: &str,
+		filter: &Filter,
+		params: &[Value],
+	) -> Result<Vec<MemoryRow>, Error> {
+		let rows = {
+			let tables = self.tables.lock().unwrap();
+			let mut rows = vec![];
+			for row in tables.get(table).into_iter().flatten() {
+				if eval::matches(&filter.whr, params, row)? {
+					rows.push(row.clone());
+				}
+			}
+
+			rows
+		};
+
+		eval::sort(filter, rows)
+	}
+}
+
+fn decode_params(params: &Params) -> Result<Vec<Value>, Error> {
+	params
+		.iter_to_sql()
+		.map(|p| {
+			RawValue::encode(p)
+				.and_then(|v| v.decode())
+				.map_err(Error::Unknown)
+		})
+		.collect()
+}
+
+pub(crate) fn to_memory_row<U>(item: &U) -> Result<MemoryRow, Error>
+where
+	U: ToRow + ?Sized,
+{
+	let mut columns = String::new();
+	item.insert_columns(&mut columns);
+
+	let values = item
+		.params()
+		.map(|p| RawValue::encode(p).map_err(Error::Unknown))
+		.collect::<Result<Vec<_>, _>>()?;
+
+	Ok(MemoryRow {
+		names: parse_columns(&columns),
+		values,
+	})
+}
+
+/// Returns a row which only contains the given columns, missing columns are
+/// null.
+fn project(row: &MemoryRow, columns: &[String]) -> MemoryRow {
+	let values = columns
+		.iter()
+		.map(|col| match row.names.iter().position(|n| n == col) {
+			Some(i) => row.values[i].clone(),
+			None => RawValue {
+				ty: Type::UNKNOWN,
+				raw: None,
+			},
+		})
+		.collect();
+
+	MemoryRow {
+		names: columns.to_vec(),
+		values,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use crate::filter::{Direction, FilterParser};
+	use crate::table::column::ColumnKind;
+	use crate::test_util::{block_on, User};
+	use crate::{filter, row, whr};
+
+	async fn users() -> MemoryDatabase {
+		let db = MemoryDatabase::new();
+		for (id, name, age) in [
+			(1, "Anna", Some(30)),
+			(2, "Ben", None),
+			(3, "Bea", Some(25)),
+		] {
+			db.insert("users", row! { "id": id, "name": name, age })
+				.await
+				.unwrap();
+		}
+
+		db
+	}
+
+	#[test]
+	fn test_select() {
+		block_on(async {
+			let db = users().await;
+
+			let name = "B%";
+			let users: Vec<User> = db
+				.select("users", filter!("name" LIKE &name ORDER "id" DESC))
+				.await
+				.unwrap();
+			assert_eq!(
+				users.iter().map(|u| u.id).collect::<Vec<_>>(),
+				vec![3, 2]
+			);
+
+			let age: Option<i32> = None;
+			let user: User =
+				db.select_one("users", filter!(&age)).await.unwrap();
+			assert_eq!(user.name, "Ben");
+
+			let ids = [1, 3];
+			let users: Vec<User> = db
+				.select("users", filter!("id" IN &ids ORDER "age" ASC LIMIT 1))
+				.await
+				.unwrap();
+			assert_eq!(users[0].id, 3);
+
+			let ids = vec![1, 3];
+			let users: Vec<User> = db
+				.select("users", filter!("id" = ANY &ids ORDER "id" DESC))
+				.await
+				.unwrap();
+			assert_eq!(
+				users.iter().map(|u| u.id).collect::<Vec<_>>(),
+				vec![3, 1]
+			);
+
+			// the search term is matched literally
+			let term = "%";
+			let users: Vec<User> =
+				db.select("users", filter!("name" ~ term)).await.unwrap();
+			assert!(users.is_empty());
+			let term = "E";
+			let users: Vec<User> = db
+				.select("users", filter!("name" ILIKE &term))
+				.await
+				.unwrap();
+			assert!(users.is_empty());
+			let term = "b%";
+			let users: Vec<User> = db
+				.select("users", filter!("name" ILIKE &term))
+				.await
+				.unwrap();
+			assert_eq!(users.len(), 2);
+
+			// ben has no age so the group is unknown
+			let id = 1;
+			let min = 26;
+			let users: Vec<User> = db
+				.select(
+					"users",
+					filter!(NOT ("age" > &min OR &id) ORDER "id" ASC),
+				)
+				.await
+				.unwrap();
+			assert_eq!(users.iter().map(|u| u.id).collect::<Vec<_>>(), vec![3]);
+
+			let users: Vec<User> =
+				db.select("users", filter!("age" > "id")).await.unwrap();
+			assert_eq!(users.len(), 2);
+		});
+	}
+
+	#[test]
+	fn test_select_columns() {
+		block_on(async {
+			let db = users().await;
+
+			let users: Vec<(String, i32)> = db
+				.select_columns(
+					"users",
+					&["name", "id"],
+					filter!(ORDER "id" ASC),
+				)
+				.await
+				.unwrap();
+			assert_eq!(users[0], ("Anna".to_string(), 1));
+			assert_eq!(users.len(), 3);
+
+			let min = 26;
+			let ids: Vec<[i32; 1]> = db
+				.select_columns("users", &["id"], filter!("age" > &min))
+				.await
+				.unwrap();
+			assert_eq!(ids, vec![[1]]);
+
+			let res: Result<Vec<[i32; 1]>, _> =
+				db.select_columns::<_, &str>("users", &[], filter!()).await;
+			assert!(res.is_err());
+		});
+	}
+
+	#[test]
+	fn test_select_order_nulls() {
+		block_on(async {
+			let db = users().await;
+
+			let ids = |users: Vec<User>| {
+				users.into_iter().map(|u| u.id).collect::<Vec<_>>()
+			};
+
+			let users: Vec<User> =
+				db.select("users", filter!(ORDER "age" ASC)).await.unwrap();
+			assert_eq!(ids(users), vec![3, 1, 2]);
+
+			let users: Vec<User> = db
+				.select("users", filter!(ORDER "age" ASC NULLS FIRST))
+				.await
+				.unwrap();
+			assert_eq!(ids(users), vec![2, 3, 1]);
+
+			let dir = Direction::Desc;
+			let users: Vec<User> = db
+				.select("users", filter!(ORDER "age" dir NULLS LAST))
+				.await
+				.unwrap();
+			assert_eq!(ids(users), vec![1, 3, 2]);
+		});
+	}
+
+	#[test]
+	fn test_select_parsed() {
+		block_on(async {
+			let db = users().await;
+
+			let parser = FilterParser::new()
+				.column("id", ColumnKind::I32)
+				.column("name", ColumnKind::Text)
+				.column("age", ColumnKind::Option(Box::new(ColumnKind::I32)));
+
+			let filter = parser
+				.parse_query("id_in=1,2,3&name_like=B%25&age_null=false")
+				.unwrap();
+			let users: Vec<User> = db.select("users", filter).await.unwrap();
+			assert_eq!(users.len(), 1);
+			assert_eq!(users[0].id, 3);
+		});
+	}
+
+	#[test]
+	fn test_select_page() {
+		block_on(async {
+			let db = users().await;
+
+			let page: OffsetPage<User> = db
+				.select_page("users", filter!(ORDER "id" DESC), 1, 2)
+				.await
+				.unwrap();
+			assert_eq!(page.items.len(), 1);
+			assert_eq!(page.items[0].id, 1);
+			assert_eq!(page.total, 3);
+			assert_eq!(page.pages(), 2);
+		});
+	}
+
+	#[test]
+	fn test_select_keyset() {
+		block_on(async {
+			let db = users().await;
+			db.insert("users", row! { "id": 4, "name": "Bob", "age": 30 })
+				.await
+				.unwrap();
+
+			let filter = || filter!(ORDER "age" DESC "id" DESC LIMIT 2);
+
+			let page: Page<User> =
+				db.select_keyset("users", filter(), None).await.unwrap();
+			assert_eq!(
+				page.items.iter().map(|u| u.id).collect::<Vec<_>>(),
+				vec![2, 4]
+			);
+
+			// the cursor survives a roundtrip through a string
+			let cursor: Cursor =
+				page.next.unwrap().to_string().parse().unwrap();
+			let page: Page<User> = db
+				.select_keyset("users", filter(), Some(&cursor))
+				.await
+				.unwrap();
+			assert_eq!(
+				page.items.iter().map(|u| u.id).collect::<Vec<_>>(),
+				vec![1, 3]
+			);
+			assert!(page.next.is_none());
+		});
+	}
+
+	#[test]
+	fn test_update_delete() {
+		block_on(async {
+			let db = users().await;
+
+			let id = 2;
+			db.update("users", row! { "age": 40 }, whr!(&id))
+				.await
+				.unwrap();
+			let age = 40;
+			let user: User =
+				db.select_one("users", filter!(&age)).await.unwrap();
+			assert_eq!(user.id, 2);
+
+			let min = 30;
+			db.delete("users", whr!("age" >= &min)).await.unwrap();
+			assert_eq!(db.count("users", "id", filter!()).await.unwrap(), 1);
+
+			// like a connection the methods also take an ident
+			let table = Ident::from(String::from("users"));
+			assert_eq!(db.count(&table, "id", filter!()).await.unwrap(), 1);
+			db.clear(&table);
+			assert_eq!(db.count(table, "id", filter!()).await.unwrap(), 0);
+		});
+	}
+}
@@ -0,0 +1,156 @@
+use std::cmp::Ordering;
+use std::error::Error as StdError;
+
+use fallible_iterator::FallibleIterator;
+use postgres_protocol::types as pg;
+use postgres_types::{FromSql, Kind, Type};
+
+use crate::row::RawValue;
+
+type BoxError = Box<dyn StdError + Sync + Send>;
+
+impl RawValue {
+	/// Decodes the value into a type which can be compared.
+	pub fn decode(&self) -> Result<Value, BoxError> {
+		match &self.raw {
+			Some(raw) => Value::from_raw(&self.ty, raw),
+			None => Ok(Value::Null),
+		}
+	}
+}
+
+/// A decoded value which can be compared.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+	Null,
+	Bool(bool),
+	Int(i64),
+	Float(f64),
+	Text(String),
+	Bytes(Vec<u8>),
+	/// microseconds since 2000-01-01
+	Timestamp(i64),
+	/// days since 2000-01-01
+	Date(i32),
+	Array(Vec<Value>),
+}
+
+impl Value {
+	fn from_raw(ty: &Type, raw: &[u8]) -> Result<Self, BoxError> {
+		if let Kind::Array(member) = ty.kind() {
+			let array = pg::array_from_sql(raw)?;
+			return array
+				.values()
+				.map(|v| match v {
+					Some(raw) => Self::from_raw(member, raw),
+					None => Ok(Self::Null),
+				})
+				.collect()
+				.map(Self::Array);
+		}
+
+		let value = match *ty {
+			Type::BOOL => Self::Bool(bool::from_sql(ty, raw)?),
+			Type::CHAR => Self::Int(i8::from_sql(ty, raw)?.into()),
+			Type::INT2 => Self::Int(i16::from_sql(ty, raw)?.into()),
+			Type::INT4 => Self::Int(i32::from_sql(ty, raw)?.into()),
+			Type::INT8 => Self::Int(i64::from_sql(ty, raw)?),
+			Type::OID => Self::Int(u32::from_sql(ty, raw)?.into()),
+			Type::FLOAT4 => Self::Float(f32::from_sql(ty, raw)?.into()),
+			Type::FLOAT8 => Self::Float(f64::from_sql(ty, raw)?),
+			Type::BYTEA => Self::Bytes(raw.to_vec()),
+			Type::TIMESTAMP | Type::TIMESTAMPTZ => {
+				Self::Timestamp(pg::timestamp_from_sql(raw)?)
+			}
+			Type::DATE => Self::Date(pg::date_from_sql(raw)?),
+			// jsonb is prefixed with a version byte
+			Type::JSONB => Self::Text(String::from_utf8(
+				raw.get(1..).unwrap_or_default().to_vec(),
+			)?),
+			_ => Self::Text(String::from_utf8(raw.to_vec())?),
+		};
+
+		Ok(value)
+	}
+
+	pub fn is_null(&self) -> bool {
+		matches!(self, Self::Null)
+	}
+
+	pub fn as_int(&self) -> Option<i64> {
+		match self {
+			Self::Int(i) => Some(*i),
+			_ => None,
+		}
+	}
+
+	pub fn as_array(&self) -> Option<&[Value]> {
+		match self {
+			Self::Array(a) => Some(a),
+			_ => None,
+		}
+	}
+
+	pub fn as_text(&self) -> Option<&str> {
+		match self {
+			Self::Text(s) => Some(s),
+			_ => None,
+		}
+	}
+
+	/// Compares two values like postgres would, returns `None` if one of the
+	/// values is null or if they can't be compared.
+	pub fn compare(&self, other: &Self) -> Option<Ordering> {
+		match (self, other) {
+			(Self::Null, _) | (_, Self::Null) => None,
+			(Self::Bool(a), Self::Bool(b)) => Some(a.cmp(b)),
+			(Self::Int(a), Self::Int(b)) => Some(a.cmp(b)),
+			(Self::Int(a), Self::Float(b)) => (*a as f64).partial_cmp(b),
+			(Self::Float(a), Self::Int(b)) => a.partial_cmp(&(*b as f64)),
+			(Self::Float(a), Self::Float(b)) => a.partial_cmp(b),
+			(Self::Text(a), Self::Text(b)) => Some(a.cmp(b)),
+			(Self::Bytes(a), Self::Bytes(b)) => Some(a.cmp(b)),
+			(Self::Timestamp(a), Self::Timestamp(b)) => Some(a.cmp(b)),
+			(Self::Date(a), Self::Date(b)) => Some(a.cmp(b)),
+			(Self::Array(a), Self::Array(b)) => {
+				for (a, b) in a.iter().zip(b) {
+					match a.compare(b)? {
+						Ordering::Equal => continue,
+						o => return Some(o),
+					}
+				}
+
+				Some(a.len().cmp(&b.len()))
+			}
+			_ => None,
+		}
+	}
+
+	/// Returns `None` if one of the values is null.
+	pub fn sql_eq(&self, other: &Self) -> Option<bool> {
+		self.compare(other).map(Ordering::is_eq)
+	}
+}
+
+/// Matches a LIKE pattern, the escape character is `\`.
+pub fn like(text: &str, pattern: &str) -> bool {
+	let text: Vec<char> = text.chars().collect();
+	let pattern: Vec<char> = pattern.chars().collect();
+
+	like_inner(&text, &pattern)
+}
+
+fn like_inner(text: &[char], pattern: &[char]) -> bool {
+	let Some((first, rest)) = pattern.split_first() else {
+		return text.is_empty();
+	};
+
+	match first {
+		'%' => (0..=text.len()).any(|i| like_inner(&text[i..], rest)),
+		'_' => !text.is_empty() && like_inner(&text[1..], rest),
+		'\\' if !rest.is_empty() => {
+			text.first() == Some(&rest[0]) && like_inner(&text[1..], &rest[1..])
+		}
+		c => text.first() == Some(c) && like_inner(&text[1..], rest),
+	}
+}
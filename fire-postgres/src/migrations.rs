@@ -4,6 +4,11 @@
 //!
 //! A migration is an sql script which can be executed on the database
 //! this script is only executed once and then stored in the database.
+//!
+//! Migrations which use the trigram operators or indexes need to create the
+//! `pg_trgm` extension themselves with
+//! `CREATE EXTENSION IF NOT EXISTS pg_trgm`, this needs the extension to be
+//! installed on the server and before postgres 13 a superuser.
 
 use crate::{connection::ConnectionOwned, filter, table::Table, Error};
 
@@ -70,7 +75,7 @@ impl Migrations {
 		}
 
 		// else execute it
-		conn.batch_execute(&sql).await?;
+		conn.batch_execute(sql).await?;
 
 		table
 			.insert(row! {
@@ -0,0 +1,645 @@
+//! A scripted connection for tests
+//!
+//! A [`MockConnection`] does not talk to a database. Instead every statement
+//! it receives is checked against a list of expectations, which contain the
+//! exact sql, optionally the params and the rows or the error which should be
+//! returned.
+//!
+//! Since the sql is compared exactly this also catches changes in the sql
+//! generated by [`Connection`] or the [`filter!`](crate::filter) macro.
+//!
+//! Requires the `testing` feature.
+//!
+//! ## Example
+//! ```
+//! # // the derives resolve to `crate::` inside of doc tests
+//! # use fire_postgres::{macros, row};
+//! use fire_postgres::mock::{Expectation, MockConnection};
+//! use fire_postgres::{filter, FromRow};
+//!
+//! #[derive(Debug, FromRow)]
+//! struct User {
+//! 	id: i32,
+//! 	name: String,
+//! }
+//!
+//! # fn main() {
+//! # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+//! let mock = MockConnection::new();
+//! mock.expect(
+//! 	Expectation::new(r#"SELECT "id", "name" FROM "users" WHERE "id" = $1"#)
+//! 		.params(&[&1i32])
+//! 		.row(row! { "id": 1i32, "name": "Anna" }),
+//! );
+//!
+//! let id = 1i32;
+//! let users: Vec<User> =
+//! 	mock.connection().select("users", filter!(&id)).await.unwrap();
+//! assert_eq!(users[0].name, "Anna");
+//!
+//! mock.verify().unwrap();
+//! # });
+//! # }
+//! ```
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use postgres_types::{BorrowToSql, ToSql};
+
+use crate::memory::to_memory_row;
+use crate::row::{MemoryRow, RawValue, ToRow};
+use crate::{Connection, Error, Row};
+
+/// A connection which returns scripted responses.
+///
+/// A statement which does not match the next expectation returns an error.
+/// Call [`MockConnection::verify`] at the end of a test to make sure every
+/// statement matched and all expectations were met.
+#[derive(Debug, Default)]
+pub struct MockConnection {
+	expectations: Mutex<VecDeque<Expectation>>,
+	/// Statements which did not match an expectation
+	failures: Mutex<Vec<String>>,
+}
+
+impl MockConnection {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Adds an expectation, expectations need to be met in the order they
+	/// were added.
+	pub fn expect(&self, expectation: Expectation) -> &Self {
+		self.expectations.lock().unwrap().push_back(expectation);
+
+		self
+	}
+
+	/// Returns a connection which uses this mock.
+	///
+	/// Only the typed methods like [`Connection::select`] and
+	/// [`Connection::batch_execute`] are supported, all methods which take a
+	/// statement return an error.
+	pub fn connection(&self) -> Connection<'_> {
+		Connection::from_mock(self)
+	}
+
+	/// Returns an error if a statement did not match its expectation or if
+	/// there are expectations which were not met.
+	pub fn verify(&self) -> Result<(), Error> {
+		let failures = self.failures.lock().unwrap();
+		if let Some(failure) = failures.first() {
+			return Err(Error::Unknown(
+				format!(
+					"{} statement(s) did not match, first: {}",
+					failures.len(),
+					failure
+				)
+				.into(),
+			));
+		}
+
+		let expectations = self.expectations.lock().unwrap();
+		if let Some(exp) = expectations.front() {
+			return Err(Error::Unknown(
+				format!(
+					"{} expectation(s) were not met, next expected sql: {}",
+					expectations.len(),
+					exp.sql
+				)
+				.into(),
+			));
+		}
+
+		Ok(())
+	}
+
+	/// ## Panics
+	/// If [`MockConnection::verify`] returns an error.
+	pub fn assert_done(&self) {
+		if let Err(e) = self.verify() {
+			panic!("{e}");
+		}
+	}
+
+	pub(crate) fn query<P, I>(
+		&self,
+		sql: &str,
+		params: I,
+	) -> Result<Vec<Row>, Error>
+	where
+		P: BorrowToSql,
+		I: IntoIterator<Item = P>,
+	{
+		self.next(sql, params)
+			.map(|exp| exp.rows.into_iter().map(Row::from).collect())
+	}
+
+	pub(crate) fn execute<P, I>(
+		&self,
+		sql: &str,
+		params: I,
+	) -> Result<u64, Error>
+	where
+		P: BorrowToSql,
+		I: IntoIterator<Item = P>,
+	{
+		self.next(sql, params)
+			.map(|exp| exp.affected.unwrap_or(exp.rows.len() as u64))
+	}
+
+	/// Returns the next expectation if it matches the statement.
+	fn next<P, I>(&self, sql: &str, params: I) -> Result<Expectation, Error>
+	where
+		P: BorrowToSql,
+		I: IntoIterator<Item = P>,
+	{
+		let mut expectations = self.expectations.lock().unwrap();
+
+		let Some(exp) = expectations.front() else {
+			return Err(self.fail(format!("unexpected sql: {sql}")));
+		};
+
+		if exp.sql != sql {
+			return Err(self.fail(format!(
+				"unexpected sql: {sql}, expected: {}",
+				exp.sql
+			)));
+		}
+
+		if let Some(expected) = &exp.params {
+			let params = params
+				.into_iter()
+				.map(|p| RawValue::encode(p.borrow_to_sql()))
+				.collect::<Result<Vec<_>, _>>()
+				.map_err(Error::Unknown)?;
+
+			if *expected != params {
+				return Err(self.fail(format!(
+					"unexpected params for sql: {sql}, expected: \
+					{expected:?}, got: {params:?}"
+				)));
+			}
+		}
+
+		let exp = expectations.pop_front().unwrap();
+		match exp.error {
+			Some(e) => Err(e),
+			None => Ok(exp),
+		}
+	}
+
+	/// Records a statement which did not match and returns the error for it.
+	fn fail(&self, msg: String) -> Error {
+		let err = Error::Unknown(msg.clone().into());
+		self.failures.lock().unwrap().push(msg);
+		err
+	}
+}
+
+/// An expected statement and the response to it.
+#[derive(Debug)]
+pub struct Expectation {
+	sql: String,
+	params: Option<Vec<RawValue>>,
+	rows: Vec<MemoryRow>,
+	affected: Option<u64>,
+	/// Returned instead of the rows
+	error: Option<Error>,
+}
+
+impl Expectation {
+	/// Expects a statement with exactly this sql.
+	///
+	/// By default the params are not checked and no rows are returned.
+	pub fn new(sql: impl Into<String>) -> Self {
+		Self {
+			sql: sql.into(),
+			params: None,
+			rows: vec![],
+			affected: None,
+			error: None,
+		}
+	}
+
+	/// Expects the statement to be called with those params.
+	///
+	/// The params need to have exactly the same type, an `i32` does not match
+	/// an `i64`.
+	///
+	/// If a param can not be encoded the statement returns that error.
+	pub fn params(mut self, params: &[&(dyn ToSql + Sync)]) -> Self {
+		let params = params
+			.iter()
+			.map(|p| RawValue::encode(*p))
+			.collect::<Result<Vec<_>, _>>();
+
+		match params {
+			Ok(params) => self.params = Some(params),
+			Err(e) => self.invalid(Error::Unknown(e)),
+		}
+
+		self
+	}
+
+	/// Adds a row which will be returned.
+	///
+	/// If the row can not be encoded the statement returns that error.
+	pub fn row<U>(mut self, row: &U) -> Self
+	where
+		U: ToRow + ?Sized,
+	{
+		match to_memory_row(row) {
+			Ok(row) => self.rows.push(row),
+			Err(e) => self.invalid(e),
+		}
+
+		self
+	}
+
+	/// Sets the number of rows the statement affected.
+	///
+	/// By default this is the number of rows returned.
+	pub fn affected(mut self, affected: u64) -> Self {
+		self.affected = Some(affected);
+		self
+	}
+
+	/// Returns an error instead of rows.
+	pub fn error(mut self, error: Error) -> Self {
+		self.error = Some(error);
+		self
+	}
+
+	/// Keeps the first error, so an invalid param or row is not hidden.
+	fn invalid(&mut self, error: Error) {
+		self.error.get_or_insert(error);
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use crate::filter::{Aggregate, Aggregates, Comparison, Join};
+	use crate::row::RowBuilder;
+	use crate::test_util::{block_on, User};
+	use crate::{filter, row, whr, FromJoinedRow, FromRow, Ident};
+	use postgres_types::Type;
+
+	#[test]
+	fn test_select_and_update() {
+		let mock = MockConnection::new();
+		mock.expect(
+			Expectation::new(
+				r#"SELECT "id", "name", "age" FROM "users" WHERE "name" LIKE $1 ORDER BY "id" ASC LIMIT 10"#,
+			)
+			.params(&[&"%nn%".to_string()])
+			.row(row! { "id": 1i32, "name": "Anna", "age": 30i32 }),
+		)
+		.expect(
+			Expectation::new(
+				r#"UPDATE "users" SET "name" = $1 WHERE "id" = $2"#,
+			)
+			.params(&[&"Ann", &1i32]),
+		);
+
+		block_on(async {
+			let conn = mock.connection();
+
+			let name = "nn";
+			let users: Vec<User> = conn
+				.select("users", filter!("name" ~ name ORDER "id" ASC LIMIT 10))
+				.await
+				.unwrap();
+			assert_eq!(users.len(), 1);
+			assert_eq!(users[0].id, 1);
+			assert_eq!(users[0].name, "Anna");
+
+			let id = 1i32;
+			conn.update("users", row! { "name": "Ann" }, whr!(&id))
+				.await
+				.unwrap();
+		});
+
+		mock.assert_done();
+	}
+
+	#[test]
+	fn test_select_columns() {
+		let mock = MockConnection::new();
+		mock.expect(
+			Expectation::new(
+				r#"SELECT "id", "na""me" FROM "users" WHERE "id" > $1"#,
+			)
+			.params(&[&0i32])
+			.row(row! { "id": 1i32, "name": "Anna" }),
+		);
+
+		block_on(async {
+			let conn = mock.connection();
+			let id = 0i32;
+
+			let users: Vec<(i32, String)> = conn
+				.select_columns("users", &["id", "na\"me"], filter!("id" > &id))
+				.await
+				.unwrap();
+			assert_eq!(users, vec![(1, "Anna".to_string())]);
+
+			// fails before anything is sent
+			let res: Result<Vec<[i32; 1]>, _> = conn
+				.select_columns::<_, &str>("users", &[], filter!())
+				.await;
+			assert!(res.is_err());
+		});
+
+		mock.assert_done();
+	}
+
+	#[test]
+	fn test_aggregate() {
+		let mock = MockConnection::new();
+		mock.expect(
+			Expectation::new(
+				r#"SELECT "status", COUNT(*), SUM("amount")::int8 AS "total" FROM "orders" WHERE "amount" > $1 GROUP BY "status" HAVING (COUNT(*) >= $2) ORDER BY "total" DESC LIMIT $3"#,
+			)
+			.params(&[&0i32, &2i64, &10i64])
+			.row(row! { "status": "paid", "count": 3i64, "total": 60i64 }),
+		)
+		.expect(
+			Expectation::new(
+				r#"SELECT COUNT(DISTINCT "user_id"), MAX("amount") FROM "orders""#,
+			)
+			.row(row! { "count": 2i64, "max": 30i32 }),
+		);
+
+		block_on(async {
+			let conn = mock.connection();
+			let zero = 0i32;
+			let min = 2i64;
+			let limit = 10i64;
+
+			let aggregates = Aggregates::new()
+				.group_by("status")
+				.select(Aggregate::count_all())
+				.select(
+					Aggregate::sum("amount").cast(Type::INT8).alias("total"),
+				)
+				.having(Aggregate::count_all(), Comparison::Gte, &min);
+			let stats: Vec<(String, i64, i64)> = conn
+				.aggregate(
+					"orders",
+					&aggregates,
+					filter!("amount" > &zero ORDER "total" DESC LIMIT &limit),
+				)
+				.await
+				.unwrap();
+			assert_eq!(stats, vec![("paid".to_string(), 3, 60)]);
+
+			let aggregates = Aggregates::new()
+				.select(Aggregate::count_distinct("user_id"))
+				.select(Aggregate::max("amount"));
+			let (users, max): (i64, Option<i32>) = conn
+				.aggregate_one("orders", &aggregates, filter!())
+				.await
+				.unwrap();
+			assert_eq!((users, max), (2, Some(30)));
+		});
+
+		mock.assert_done();
+	}
+
+	#[test]
+	fn test_select_join() {
+		#[derive(Debug, FromRow)]
+		struct Team {
+			id: i32,
+			name: String,
+		}
+
+		#[derive(Debug, FromJoinedRow)]
+		struct UserWithTeam {
+			user: User,
+			team: Option<Team>,
+		}
+
+		#[derive(Debug, FromRow)]
+		struct Nick {
+			nick: Option<String>,
+		}
+
+		let sql = r#"SELECT "u"."id", "u"."name", "u"."age", "t"."id", "t"."name", "t"."id" FROM "users" AS "u" LEFT JOIN "teams" AS "t" ON "t"."id" = "u"."team_id" WHERE "u"."name" = $1 AND "t"."id" != "u"."id" ORDER BY "u"."id" ASC"#;
+		let name = "Anna";
+		let (age, red) = (30i32, "Red");
+		let (none, no_name) = (None::<i32>, None::<String>);
+		let mut matched = RowBuilder::new();
+		matched
+			.push("id", &1i32)
+			.push("name", &name)
+			.push("age", &age)
+			.push("id", &2i32)
+			.push("name", &red)
+			.push("id", &2i32);
+		let mut unmatched = RowBuilder::new();
+		unmatched
+			.push("id", &3i32)
+			.push("name", &name)
+			.push("age", &none)
+			.push("id", &none)
+			.push("name", &no_name)
+			.push("id", &none);
+		// the nick is null but the join matched
+		let mut null_nick = RowBuilder::new();
+		null_nick
+			.push("id", &1i32)
+			.push("name", &name)
+			.push("age", &age)
+			.push("nick", &no_name)
+			.push("user_id", &1i32);
+
+		let mock = MockConnection::new();
+		mock.expect(
+			Expectation::new(sql)
+				.params(&[&name])
+				.row(&matched)
+				.row(&unmatched),
+		)
+		.expect(Expectation::new(sql).row(&matched).row(&unmatched))
+		.expect(
+			Expectation::new(
+				r#"SELECT "u"."id", "u"."name", "u"."age", "n"."nick", "n"."user_id" FROM "users" AS "u" LEFT JOIN "nicks" AS "n" ON "n"."user_id" = "u"."id""#,
+			)
+			.row(&null_nick),
+		);
+
+		block_on(async {
+			let conn = mock.connection();
+			let join = Join::new("users", "u").left(
+				"teams",
+				"t",
+				"id",
+				Ident::qualified("u", "team_id"),
+			);
+			let filter = filter!(
+				"u"."name" = &name AND "t"."id" != "u"."id"
+				ORDER "u"."id" ASC
+			);
+
+			let rows: Vec<(User, Option<Team>)> =
+				conn.select_join(&join, &filter).await.unwrap();
+			assert_eq!(rows.len(), 2);
+			assert_eq!(rows[0].0.id, 1);
+			assert_eq!(rows[0].1.as_ref().unwrap().name, "Red");
+			assert_eq!(rows[1].0.id, 3);
+			assert!(rows[1].1.is_none());
+
+			let rows: Vec<UserWithTeam> =
+				conn.select_join(&join, &filter).await.unwrap();
+			assert_eq!(rows[0].user.name, "Anna");
+			assert_eq!(rows[0].team.as_ref().unwrap().id, 2);
+			assert!(rows[1].team.is_none());
+
+			let join = Join::new("users", "u").left(
+				"nicks",
+				"n",
+				"user_id",
+				Ident::qualified("u", "id"),
+			);
+			let rows: Vec<(User, Option<Nick>)> =
+				conn.select_join(&join, filter!()).await.unwrap();
+			assert!(rows[0].1.as_ref().unwrap().nick.is_none());
+
+			// every table needs a part
+			let res = conn.select_join::<(User, Team, Team)>(&join, &filter);
+			assert!(matches!(res.await, Err(Error::Deserialize(_))));
+		});
+
+		mock.assert_done();
+	}
+
+	#[test]
+	fn test_select_page_and_count() {
+		let mock = MockConnection::new();
+		mock.expect(
+			Expectation::new(
+				r#"SELECT "id", "name", "age", COUNT(*) OVER () AS "__total" FROM "users" WHERE "id" > $1 ORDER BY "id" ASC LIMIT 2 OFFSET 2"#,
+			)
+			.params(&[&0i32])
+			.row(row! { "id": 3i32, "name": "Bea", "age": 25i32, "__total": 3i64 }),
+		)
+		.expect(
+			Expectation::new(
+				r#"SELECT "id", "name", "age", COUNT(*) OVER () AS "__total" FROM "users" WHERE "id" > $1 ORDER BY "id" ASC LIMIT 2 OFFSET 4"#,
+			)
+			.params(&[&0i32]),
+		)
+		.expect(
+			Expectation::new(
+				r#"SELECT COUNT(*) FROM "users" WHERE "id" > $1"#,
+			)
+			.params(&[&0i32])
+			.row(row! { "count": 3i64 }),
+		)
+		.expect(
+			Expectation::new(r#"SELECT COUNT("id") FROM "users" WHERE "id" > $1"#)
+				.params(&[&0i32])
+				.row(row! { "count": 3i64 }),
+		);
+
+		block_on(async {
+			let conn = mock.connection();
+			let id = 0i32;
+
+			// the limit param of the filter is replaced
+			let limit = 10;
+			let filter = filter!("id" > &id ORDER "id" ASC LIMIT &limit);
+			let page = conn.select_page::<User>("users", &filter, 1, 2).await;
+			let page = page.unwrap();
+			assert_eq!(page.items.len(), 1);
+			assert_eq!(page.total, 3);
+			assert_eq!(page.pages(), 2);
+			assert!(!page.has_next());
+
+			// a page after the last row needs a second query
+			let page = conn.select_page::<User>("users", &filter, 2, 2).await;
+			let page = page.unwrap();
+			assert!(page.items.is_empty());
+			assert_eq!(page.total, 3);
+
+			let count = conn.count("users", "id", filter!("id" > &id)).await;
+			assert_eq!(count.unwrap(), 3);
+		});
+
+		mock.assert_done();
+	}
+
+	#[test]
+	fn test_error() {
+		let mock = MockConnection::new();
+		mock.expect(
+			Expectation::new(r#"DELETE FROM "users""#)
+				.error(Error::Unknown("failed".into())),
+		)
+		.expect(
+			// the error wins independent of the order
+			Expectation::new(r#"SELECT "id", "name", "age" FROM "users""#)
+				.error(Error::Unknown("failed".into()))
+				.row(row! { "id": 1i32, "name": "Anna", "age": 30i32 }),
+		);
+
+		block_on(async {
+			let conn = mock.connection();
+			let res = conn.delete("users", whr!()).await;
+			assert!(matches!(res, Err(Error::Unknown(_))));
+
+			let res = conn.select::<User>("users", filter!()).await;
+			assert!(matches!(res, Err(Error::Unknown(_))));
+		});
+
+		mock.verify().unwrap();
+	}
+
+	#[test]
+	fn test_unexpected_sql() {
+		let mock = MockConnection::new();
+		mock.expect(Expectation::new(r#"DELETE FROM "other""#));
+
+		block_on(async {
+			let res = mock.connection().delete("users", whr!()).await;
+			assert!(matches!(res, Err(Error::Unknown(_))));
+
+			let id = 1i32;
+			let res = mock.connection().delete("other", whr!(&id)).await;
+			assert!(matches!(res, Err(Error::Unknown(_))));
+		});
+
+		let err = mock.verify().unwrap_err().to_string();
+		assert!(err.contains("2 statement(s) did not match"), "{err}");
+		assert!(err.contains(r#"unexpected sql: DELETE FROM "users""#));
+	}
+
+	#[test]
+	fn test_unmet_expectations() {
+		let mock = MockConnection::new();
+		mock.expect(
+			Expectation::new(r#"DELETE FROM "users" WHERE "id" = $1"#)
+				.params(&[&2i32]),
+		);
+
+		block_on(async {
+			let id = 1i32;
+			let res = mock.connection().delete("users", whr!(&id)).await;
+			assert!(matches!(res, Err(Error::Unknown(_))));
+		});
+
+		let err = mock.verify().unwrap_err().to_string();
+		assert!(err.contains("unexpected params"), "{err}");
+
+		// dropping a mock with unmet expectations does not panic
+		let mock = MockConnection::new();
+		mock.expect(Expectation::new(r#"DELETE FROM "users""#));
+		let err = mock.verify().unwrap_err().to_string();
+		assert!(err.contains("1 expectation(s) were not met"), "{err}");
+	}
+}
@@ -36,31 +36,92 @@ where
 	}
 }
 
+// tuples don't know their column names so the values are read by position
 macro_rules! impl_tuple {
-	($($name:ident),*) => {
+	($($name:ident $idx:tt),*) => {
 		impl<'r, $($name),*> FromRow<'r> for ($($name),*)
 		where
 			$($name: FromSql<'r>),*
 		{
 			fn from_row(row: &'r Row) -> Result<Self, Box<dyn StdError + Sync + Send>> {
-				Ok(($(row.try_get(stringify!($name))?),*))
+				Ok(($(row.try_get($idx)?),*))
 			}
 		}
 	};
 }
 
-impl_tuple!(A, B);
-impl_tuple!(A, B, C);
-impl_tuple!(A, B, C, D);
-impl_tuple!(A, B, C, D, E);
-impl_tuple!(A, B, C, D, E, F);
-impl_tuple!(A, B, C, D, E, F, G);
-impl_tuple!(A, B, C, D, E, F, G, H);
-impl_tuple!(A, B, C, D, E, F, G, H, I);
-impl_tuple!(A, B, C, D, E, F, G, H, I, J);
-impl_tuple!(A, B, C, D, E, F, G, H, I, J, K);
-impl_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
-impl_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M);
-impl_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N);
-impl_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O);
-impl_tuple!(A, B, C, D, E, F, G, H, I, J, K, L, M, N, O, P);
+impl_tuple!(A 0, B 1);
+impl_tuple!(A 0, B 1, C 2);
+impl_tuple!(A 0, B 1, C 2, D 3);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13, O 14);
+impl_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13, O 14, P 15);
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use crate::connection::Error;
+	use crate::row::{MemoryRow, RawValue};
+
+	fn memory_row(
+		columns: &[(&str, &(dyn postgres_types::ToSql + Sync))],
+	) -> Row {
+		MemoryRow {
+			names: columns.iter().map(|(n, _)| n.to_string()).collect(),
+			values: columns
+				.iter()
+				.map(|(_, v)| RawValue::encode(*v).unwrap())
+				.collect(),
+		}
+		.into()
+	}
+
+	#[test]
+	fn test_tuple_by_position() {
+		// the names don't matter, only the order of the columns
+		let row =
+			memory_row(&[("name", &"Anna"), ("id", &1i32), ("age", &30i64)]);
+
+		let (name, id, age) = <(String, i32, i64)>::from_row(&row).unwrap();
+		assert_eq!((name.as_str(), id, age), ("Anna", 1, 30));
+
+		// columns after the tuple are ignored
+		let (name, id) = <(String, i32)>::from_row(&row).unwrap();
+		assert_eq!((name.as_str(), id), ("Anna", 1));
+
+		let [name] = <[String; 1]>::from_row(&row).unwrap();
+		assert_eq!(name, "Anna");
+
+		// the types need to match the position
+		assert!(<(i32, String)>::from_row(&row).is_err());
+
+		let short = memory_row(&[("id", &1i32)]);
+		assert!(<(i32, i32)>::from_row(&short).is_err());
+	}
+
+	#[test]
+	fn test_memory_try_get() {
+		let row = memory_row(&[("name", &"Anna"), ("id", &1i32)]);
+
+		assert_eq!(row.try_get::<_, i32>("id").unwrap(), 1);
+		assert!(matches!(
+			row.try_get::<_, String>("id"),
+			Err(Error::Deserialize(_))
+		));
+		assert!(matches!(
+			row.try_get::<_, i32>("age"),
+			Err(Error::Deserialize(_))
+		));
+		assert!(row.try_get::<_, i32>(2).is_err());
+	}
+}
@@ -0,0 +1,119 @@
+use std::error::Error as StdError;
+
+use super::{FromRowOwned, NamedColumns, Row};
+
+type BoxError = Box<dyn StdError + Sync + Send>;
+
+/// A row of a select with joins, the columns of every table are read into
+/// their own part.
+///
+/// This is implemented for tuples like `(User, Option<Team>)` and can be
+/// derived for structs where every field is a [`JoinPart`].
+///
+/// ## Example
+/// ```
+/// # // the derives resolve to `crate::` inside of doc tests
+/// # use fire_postgres::{filter, row};
+/// use fire_postgres::{FromJoinedRow, FromRow};
+/// #[derive(FromRow)]
+/// struct User {
+/// 	id: i32,
+/// 	name: String,
+/// }
+///
+/// #[derive(FromRow)]
+/// struct Team {
+/// 	id: i32,
+/// 	name: String,
+/// }
+///
+/// // the fields are in the order of the joins
+/// #[derive(FromJoinedRow)]
+/// struct UserWithTeam {
+/// 	user: User,
+/// 	team: Option<Team>,
+/// }
+/// # fn main() {}
+/// ```
+pub trait FromJoinedRow: Sized {
+	/// Returns the columns of every table, in the order of the joins.
+	fn tables() -> Vec<&'static str>;
+
+	/// Gets one row per table, together with whether the table matched, see
+	/// [`JoinPart::from_part`].
+	fn from_parts(parts: Vec<(Row, bool)>) -> Result<Self, BoxError>;
+}
+
+/// The columns of one table in a joined row.
+///
+/// If the part is an `Option` and the table was not matched by a `LEFT JOIN`
+/// it is `None`.
+pub trait JoinPart: Sized {
+	/// should return something like "id", "name", "email"
+	fn select_columns() -> &'static str;
+
+	/// `matched` is false if a `LEFT JOIN` found no row, all columns are null
+	/// in that case. A match is detected with the key of the join, so a
+	/// matched row where every selected column is null is still matched.
+	fn from_part(row: Row, matched: bool) -> Result<Self, BoxError>;
+}
+
+impl<T> JoinPart for T
+where
+	T: FromRowOwned + NamedColumns,
+{
+	fn select_columns() -> &'static str {
+		T::select_columns()
+	}
+
+	fn from_part(row: Row, _matched: bool) -> Result<Self, BoxError> {
+		T::from_row_owned(row)
+	}
+}
+
+impl<T> JoinPart for Option<T>
+where
+	T: FromRowOwned + NamedColumns,
+{
+	fn select_columns() -> &'static str {
+		T::select_columns()
+	}
+
+	fn from_part(row: Row, matched: bool) -> Result<Self, BoxError> {
+		if !matched {
+			return Ok(None);
+		}
+
+		T::from_row_owned(row).map(Some)
+	}
+}
+
+macro_rules! impl_tuple {
+	($($name:ident),*) => {
+		impl<$($name),*> FromJoinedRow for ($($name),*)
+		where
+			$($name: JoinPart),*
+		{
+			fn tables() -> Vec<&'static str> {
+				vec![$($name::select_columns()),*]
+			}
+
+			fn from_parts(
+				parts: Vec<(Row, bool)>,
+			) -> Result<Self, BoxError> {
+				let mut parts = parts.into_iter();
+				Ok(($({
+					let (row, matched) =
+						parts.next().ok_or("missing part of joined row")?;
+					$name::from_part(row, matched)?
+				}),*))
+			}
+		}
+	};
+}
+
+impl_tuple!(A, B);
+impl_tuple!(A, B, C);
+impl_tuple!(A, B, C, D);
+impl_tuple!(A, B, C, D, E);
+impl_tuple!(A, B, C, D, E, F);
@@ -0,0 +1,44 @@
+use postgres_types::{FromSql, Type, WrongType};
+use tokio_postgres::row::RowIndex;
+
+use super::{RawValue, Row, RowInner};
+use crate::connection::Error;
+
+/// A row which does not come from postgres but holds it's values in memory.
+#[derive(Debug, Clone)]
+pub(crate) struct MemoryRow {
+	pub names: Vec<String>,
+	pub values: Vec<RawValue>,
+}
+
+impl MemoryRow {
+	pub(super) fn try_get<'a, I, T>(&'a self, idx: I) -> Result<T, Error>
+	where
+		I: RowIndex + std::fmt::Display,
+		T: FromSql<'a>,
+	{
+		let i = idx.__idx(&self.names).ok_or_else(|| {
+			Error::Deserialize(format!("column {idx} not found").into())
+		})?;
+		let value = &self.values[i];
+
+		// a column which was never set has no type, it is handled like a
+		// null with whatever type is requested
+		if value.ty != Type::UNKNOWN && !T::accepts(&value.ty) {
+			return Err(Error::Deserialize(Box::new(WrongType::new::<T>(
+				value.ty.clone(),
+			))));
+		}
+
+		T::from_sql_nullable(&value.ty, value.raw.as_deref())
+			.map_err(Error::Deserialize)
+	}
+}
+
+impl From<MemoryRow> for Row {
+	fn from(row: MemoryRow) -> Self {
+		Self {
+			row: RowInner::Memory(row),
+		}
+	}
+}
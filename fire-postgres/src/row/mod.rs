@@ -1,22 +1,32 @@
 mod from;
+mod joined;
+#[cfg(feature = "testing")]
+mod memory;
+mod raw;
 mod to;
 
 use std::{
 	error::Error as StdError,
 	fmt::Write,
 	pin::Pin,
+	sync::Arc,
 	task::{Context, Poll},
 };
 
 use futures_util::Stream;
 use pin_project_lite::pin_project;
-use postgres_types::{FromSql, ToSql};
+use postgres_types::{FromSql, ToSql, Type};
 use tokio_postgres::row::RowIndex;
 pub use tokio_postgres::Column;
 
 use crate::connection::Error;
+use crate::Ident;
 
 pub use from::{FromRow, FromRowOwned};
+pub use joined::{FromJoinedRow, JoinPart};
+#[cfg(feature = "testing")]
+pub(crate) use memory::MemoryRow;
+pub use raw::RawValue;
 pub use to::{ToRow, ToRowStatic};
 
 pub trait NamedColumns {
@@ -24,26 +34,105 @@ pub trait NamedColumns {
 	fn select_columns() -> &'static str;
 }
 
+/// Parses a list of quoted column names like `"id", "name"`
+pub(crate) fn parse_columns(s: &str) -> Vec<String> {
+	let mut columns = vec![];
+	let mut chars = s.chars().peekable();
+
+	while let Some(c) = chars.next() {
+		if c != '"' {
+			continue;
+		}
+
+		let mut name = String::new();
+		while let Some(c) = chars.next() {
+			match c {
+				'"' if chars.peek() == Some(&'"') => {
+					chars.next();
+					name.push('"');
+				}
+				'"' => break,
+				c => name.push(c),
+			}
+		}
+
+		columns.push(name);
+	}
+
+	columns
+}
+
 #[derive(Debug)]
 #[repr(transparent)]
 pub struct Row {
-	row: tokio_postgres::Row,
+	row: RowInner,
+}
+
+#[derive(Debug)]
+enum RowInner {
+	Pg(tokio_postgres::Row),
+	#[cfg(feature = "testing")]
+	Memory(MemoryRow),
+	Slice(RowSlice),
+}
+
+/// A part of a row, like the columns of one table of a joined row.
+#[derive(Debug)]
+struct RowSlice {
+	/// Never a slice itself
+	row: Arc<Row>,
+	start: usize,
+	len: usize,
+}
+
+impl RowSlice {
+	/// Returns the index of the column in the original row.
+	fn index<I>(&self, idx: &I) -> Option<usize>
+	where
+		I: RowIndex,
+	{
+		let range = self.start..self.start + self.len;
+		let i = match &self.row.row {
+			RowInner::Pg(row) => idx.__idx(&row.columns()[range]),
+			#[cfg(feature = "testing")]
+			RowInner::Memory(row) => idx.__idx(&row.names[range]),
+			RowInner::Slice(_) => None,
+		}?;
+
+		Some(self.start + i)
+	}
 }
 
 impl Row {
 	/// Returns information about the columns of data in the row.
+	///
+	/// Rows which don't come from postgres, like the ones returned by the
+	/// in memory backend of the `testing` feature, have no column information
+	/// and return an empty slice.
 	pub fn columns(&self) -> &[Column] {
-		self.row.columns()
+		match &self.row {
+			RowInner::Pg(row) => row.columns(),
+			#[cfg(feature = "testing")]
+			RowInner::Memory(_) => &[],
+			RowInner::Slice(s) => {
+				s.row.columns().get(s.start..s.start + s.len).unwrap_or(&[])
+			}
+		}
 	}
 
 	/// Determines if the row contains no values.
 	pub fn is_empty(&self) -> bool {
-		self.row.is_empty()
+		self.len() == 0
 	}
 
 	/// Returns the number of values in the row.
 	pub fn len(&self) -> usize {
-		self.row.len()
+		match &self.row {
+			RowInner::Pg(row) => row.len(),
+			#[cfg(feature = "testing")]
+			RowInner::Memory(row) => row.values.len(),
+			RowInner::Slice(s) => s.len,
+		}
 	}
 
 	/// Deserializes the row.
@@ -80,25 +169,108 @@ impl Row {
 		I: RowIndex + std::fmt::Display,
 		T: FromSql<'a>,
 	{
-		self.row.get(idx)
+		match &self.row {
+			RowInner::Pg(row) => row.get(idx),
+			#[cfg(feature = "testing")]
+			RowInner::Memory(row) => match row.try_get(&idx) {
+				Ok(v) => v,
+				Err(e) => panic!("error retrieving column {}: {}", idx, e),
+			},
+			RowInner::Slice(s) => match s.index(&idx) {
+				Some(i) => s.row.get(i),
+				None => panic!("error retrieving column {}: not found", idx),
+			},
+		}
+	}
+
+	/// Splits the row into parts with the given number of columns, the
+	/// columns after the last part are ignored.
+	///
+	/// The parts share the original row, no values are copied.
+	pub(crate) fn split(self, lens: &[usize]) -> Result<Vec<Row>, Error> {
+		let expected = lens.iter().sum::<usize>();
+		if expected > self.len() {
+			return Err(Error::Deserialize(
+				format!(
+					"expected {} columns but the row has {}",
+					expected,
+					self.len()
+				)
+				.into(),
+			));
+		}
+
+		let (row, mut start) = match self.row {
+			RowInner::Slice(s) => (s.row, s.start),
+			row => (Arc::new(Row { row }), 0),
+		};
+
+		Ok(lens
+			.iter()
+			.map(|len| {
+				let slice = RowSlice {
+					row: row.clone(),
+					start,
+					len: *len,
+				};
+				start += len;
+
+				Row {
+					row: RowInner::Slice(slice),
+				}
+			})
+			.collect())
+	}
+
+	/// Returns true if the value at the index is null.
+	pub(crate) fn is_null(&self, idx: usize) -> Result<bool, Error> {
+		self.try_get::<_, Option<Ignored>>(idx).map(|v| v.is_none())
 	}
 
 	/// Like [`Row::get()`], but returns a [`Result`] rather than panicking.
-	pub fn try_get<'a, I, T>(
-		&'a self,
-		idx: I,
-	) -> Result<T, tokio_postgres::Error>
+	///
+	/// Errors of tokio-postgres are converted into an [`Error`], rows which
+	/// don't come from postgres return [`Error::Deserialize`].
+	pub fn try_get<'a, I, T>(&'a self, idx: I) -> Result<T, Error>
 	where
 		I: RowIndex + std::fmt::Display,
 		T: FromSql<'a>,
 	{
-		self.row.try_get(idx)
+		match &self.row {
+			RowInner::Pg(row) => row.try_get(idx).map_err(Error::from),
+			#[cfg(feature = "testing")]
+			RowInner::Memory(row) => row.try_get(idx),
+			RowInner::Slice(s) => match s.index(&idx) {
+				Some(i) => s.row.try_get(i),
+				None => Err(Error::Deserialize(
+					format!("column {idx} not found").into(),
+				)),
+			},
+		}
 	}
 }
 
 impl From<tokio_postgres::Row> for Row {
 	fn from(row: tokio_postgres::Row) -> Self {
-		Self { row }
+		Self {
+			row: RowInner::Pg(row),
+		}
+	}
+}
+
+/// Accepts every value without decoding it.
+struct Ignored;
+
+impl<'a> FromSql<'a> for Ignored {
+	fn from_sql(
+		_ty: &Type,
+		_raw: &'a [u8],
+	) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+		Ok(Self)
+	}
+
+	fn accepts(_ty: &Type) -> bool {
+		true
 	}
 }
 
@@ -113,7 +285,20 @@ impl FromRowOwned for Row {
 pin_project! {
 	pub struct RowStream {
 		#[pin]
-		inner: tokio_postgres::RowStream,
+		inner: RowStreamInner,
+	}
+}
+
+pin_project! {
+	#[project = RowStreamProj]
+	enum RowStreamInner {
+		Pg {
+			#[pin]
+			stream: tokio_postgres::RowStream,
+		},
+		Memory {
+			rows: std::vec::IntoIter<Row>,
+		},
 	}
 }
 
@@ -124,26 +309,39 @@ impl Stream for RowStream {
 		self: Pin<&mut Self>,
 		cx: &mut Context<'_>,
 	) -> Poll<Option<Self::Item>> {
-		let this = self.project();
-
-		match this.inner.poll_next(cx) {
-			Poll::Ready(Some(Ok(row))) => Poll::Ready(Some(Ok(row.into()))),
-			Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(e.into()))),
-			Poll::Ready(None) => Poll::Ready(None),
-			Poll::Pending => Poll::Pending,
+		match self.project().inner.project() {
+			RowStreamProj::Pg { stream } => match stream.poll_next(cx) {
+				Poll::Ready(Some(Ok(row))) => Poll::Ready(Some(Ok(row.into()))),
+				Poll::Ready(Some(Err(e))) => Poll::Ready(Some(Err(e.into()))),
+				Poll::Ready(None) => Poll::Ready(None),
+				Poll::Pending => Poll::Pending,
+			},
+			RowStreamProj::Memory { rows } => Poll::Ready(rows.next().map(Ok)),
 		}
 	}
 }
 
 impl From<tokio_postgres::RowStream> for RowStream {
-	fn from(inner: tokio_postgres::RowStream) -> Self {
-		Self { inner }
+	fn from(stream: tokio_postgres::RowStream) -> Self {
+		Self {
+			inner: RowStreamInner::Pg { stream },
+		}
+	}
+}
+
+impl From<Vec<Row>> for RowStream {
+	fn from(rows: Vec<Row>) -> Self {
+		Self {
+			inner: RowStreamInner::Memory {
+				rows: rows.into_iter(),
+			},
+		}
 	}
 }
 
 #[derive(Debug)]
 pub struct RowBuilder<'a> {
-	inner: Vec<(&'a str, &'a (dyn ToSql + Sync))>,
+	inner: Vec<(Ident, &'a (dyn ToSql + Sync))>,
 }
 
 impl<'a> RowBuilder<'a> {
@@ -153,15 +351,13 @@ impl<'a> RowBuilder<'a> {
 
 	/// Push a new column to the row.
 	///
-	/// ## Note
-	/// Do not use untrusted names this might lead to
-	/// SQL injection.
+	/// The name is quoted and escaped.
 	pub fn push(
 		&mut self,
-		name: &'a str,
+		name: impl Into<Ident>,
 		value: &'a (dyn ToSql + Sync),
 	) -> &mut Self {
-		self.inner.push((name, value));
+		self.inner.push((name.into(), value));
 
 		self
 	}
@@ -174,7 +370,7 @@ impl ToRow for RowBuilder<'_> {
 				s.push_str(", ");
 			}
 
-			write!(s, "\"{k}\"").unwrap();
+			write!(s, "{k}").unwrap();
 		}
 	}
 
@@ -194,7 +390,7 @@ impl ToRow for RowBuilder<'_> {
 				s.push_str(", ");
 			}
 
-			write!(s, "\"{k}\" = ${}", i + 1).unwrap();
+			write!(s, "{k} = ${}", i + 1).unwrap();
 		}
 	}
 
@@ -233,4 +429,23 @@ mod tests {
 			r#"UPDATE "users" SET "id" = $1, "name" = $2, "email" = $3"#
 		);
 	}
+
+	#[test]
+	fn test_parse_columns() {
+		assert_eq!(
+			parse_columns(r#""id", "na""me""#),
+			vec!["id".to_string(), "na\"me".to_string()]
+		);
+	}
+
+	#[test]
+	fn test_row_builder_escapes_names() {
+		let name = String::from(r#"name", "id"#);
+		let mut row = RowBuilder::new();
+		row.push(name, &1i32);
+
+		let mut cols = String::new();
+		row.insert_columns(&mut cols);
+		assert_eq!(cols, r#""name"", ""id""#);
+	}
 }
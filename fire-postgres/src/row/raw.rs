@@ -0,0 +1,130 @@
+use std::error::Error as StdError;
+
+use bytes::BytesMut;
+use postgres_types::{FromSql, IsNull, ToSql, Type, WrongType};
+
+type BoxError = Box<dyn StdError + Sync + Send>;
+
+/// The types a parameter is tried to be encoded with, the first one which is
+/// accepted by the parameter is used.
+const CANDIDATES: &[Type] = &[
+	Type::BOOL,
+	Type::CHAR,
+	Type::INT2,
+	Type::INT4,
+	Type::INT8,
+	Type::OID,
+	Type::FLOAT4,
+	Type::FLOAT8,
+	Type::TEXT,
+	Type::BYTEA,
+	Type::TIMESTAMP,
+	Type::TIMESTAMPTZ,
+	Type::DATE,
+	Type::JSON,
+	Type::JSONB,
+	Type::BOOL_ARRAY,
+	Type::INT2_ARRAY,
+	Type::INT4_ARRAY,
+	Type::INT8_ARRAY,
+	Type::FLOAT4_ARRAY,
+	Type::FLOAT8_ARRAY,
+	Type::TEXT_ARRAY,
+	Type::BYTEA_ARRAY,
+	Type::TIMESTAMP_ARRAY,
+	Type::TIMESTAMPTZ_ARRAY,
+	Type::DATE_ARRAY,
+	Type::JSON_ARRAY,
+	Type::JSONB_ARRAY,
+];
+
+/// A value in its binary postgres representation together with its type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RawValue {
+	pub ty: Type,
+	pub raw: Option<Vec<u8>>,
+}
+
+impl RawValue {
+	/// Encodes a value with the first type it accepts.
+	pub fn encode(value: &dyn ToSql) -> Result<Self, BoxError> {
+		for ty in CANDIDATES {
+			let mut buf = BytesMut::new();
+
+			match value.to_sql_checked(ty, &mut buf) {
+				Ok(IsNull::Yes) => {
+					return Ok(Self {
+						ty: ty.clone(),
+						raw: None,
+					})
+				}
+				Ok(IsNull::No) => {
+					return Ok(Self {
+						ty: ty.clone(),
+						raw: Some(buf.to_vec()),
+					})
+				}
+				Err(e) if e.is::<WrongType>() => continue,
+				Err(e) => return Err(e),
+			}
+		}
+
+		Err(format!("could not find a type for the value {value:?}").into())
+	}
+
+	pub fn is_null(&self) -> bool {
+		self.raw.is_none()
+	}
+}
+
+impl ToSql for RawValue {
+	fn to_sql(
+		&self,
+		_ty: &Type,
+		out: &mut BytesMut,
+	) -> Result<IsNull, BoxError> {
+		match &self.raw {
+			Some(raw) => {
+				out.extend_from_slice(raw);
+				Ok(IsNull::No)
+			}
+			None => Ok(IsNull::Yes),
+		}
+	}
+
+	fn accepts(_ty: &Type) -> bool {
+		true
+	}
+
+	fn to_sql_checked(
+		&self,
+		ty: &Type,
+		out: &mut BytesMut,
+	) -> Result<IsNull, BoxError> {
+		if *ty != self.ty {
+			return Err(Box::new(WrongType::new::<RawValue>(ty.clone())));
+		}
+
+		self.to_sql(ty, out)
+	}
+}
+
+impl<'a> FromSql<'a> for RawValue {
+	fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, BoxError> {
+		Ok(Self {
+			ty: ty.clone(),
+			raw: Some(raw.to_vec()),
+		})
+	}
+
+	fn from_sql_null(ty: &Type) -> Result<Self, BoxError> {
+		Ok(Self {
+			ty: ty.clone(),
+			raw: None,
+		})
+	}
+
+	fn accepts(_ty: &Type) -> bool {
+		true
+	}
+}
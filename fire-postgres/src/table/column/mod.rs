@@ -1,3 +1,5 @@
+use crate::Ident;
+
 mod column_type;
 pub use column_type::ColumnType;
 
@@ -30,6 +32,23 @@ impl Column {
 
 		Self { name, kind, index }
 	}
+
+	/// A tsvector column which is generated from the given text columns, with
+	/// a gin index.
+	///
+	/// The language is the text search configuration, for example `english`
+	/// or `simple`.
+	pub fn text_search(
+		name: &'static str,
+		language: &'static str,
+		columns: Vec<&'static str>,
+	) -> Self {
+		Self {
+			name,
+			kind: ColumnKind::GeneratedTsVector { language, columns },
+			index: IndexKind::Gin,
+		}
+	}
 }
 
 /*
@@ -54,6 +73,13 @@ pub enum ColumnKind {
 	TextArray,
 	Bytea,
 	Json,
+	/// A full text search document
+	TsVector,
+	/// A tsvector which is always generated from the text columns
+	GeneratedTsVector {
+		language: &'static str,
+		columns: Vec<&'static str>,
+	},
 }
 
 impl ColumnKind {
@@ -75,6 +101,7 @@ impl ColumnKind {
 			Self::TextArray => "text []",
 			Self::Bytea => "bytea",
 			Self::Json => "json",
+			Self::TsVector | Self::GeneratedTsVector { .. } => "tsvector",
 		}
 	}
 
@@ -84,6 +111,19 @@ impl ColumnKind {
 			Self::Varchar(v) => format!("({})", v),
 			Self::FixedText(v) => format!(" CHECK (length({})={})", name, v),
 			Self::Option(t) => t.value(name),
+			Self::GeneratedTsVector { language, columns } => {
+				let text = columns
+					.iter()
+					.map(|c| format!("coalesce({}, '')", Ident::new(c)))
+					.collect::<Vec<_>>()
+					.join(" || ' ' || ");
+
+				format!(
+					" GENERATED ALWAYS AS (to_tsvector('{}', {})) STORED",
+					language.replace('\'', "''"),
+					text
+				)
+			}
 			_ => String::new(),
 		}
 	}
@@ -106,6 +146,20 @@ pub enum IndexKind {
 	Unique,
 	NamedUnique(&'static str),
 	Index,
+	/// A gin index, used for tsvector, arrays and jsonb
+	Gin,
+	/// A gin trigram index, used for `LIKE`, `ILIKE` and the `%` similarity
+	/// operator.
+	///
+	/// Needs the `pg_trgm` extension, the table creation runs
+	/// `CREATE EXTENSION IF NOT EXISTS pg_trgm` which fails if the extension
+	/// is not installed on the server or the user is not allowed to create
+	/// it (before postgres 13 only superusers can).
+	Trigram,
+	/// A gist trigram index, supports ordering by distance `<->`.
+	///
+	/// Needs the `pg_trgm` extension, see [`IndexKind::Trigram`].
+	TrigramGist,
 	None,
 }
 
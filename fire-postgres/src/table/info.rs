@@ -1,4 +1,5 @@
 use super::column::Column;
+use super::util::info_data_to_sql;
 
 #[derive(Debug, Clone)]
 pub struct Info {
@@ -29,6 +30,15 @@ impl Info {
 	) -> impl ExactSizeIterator<Item = &'static str> + 'a {
 		self.data.iter().map(|v| v.name)
 	}
+
+	/// Returns the sql which creates the table and all its indexes if they
+	/// don't exist yet.
+	///
+	/// If a column has a trigram index the `pg_trgm` extension is created
+	/// first.
+	pub fn create_sql(&self, table: &str) -> String {
+		info_data_to_sql(table, &self.data)
+	}
 }
 
 #[derive(Debug, Clone)]
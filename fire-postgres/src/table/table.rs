@@ -1,23 +1,23 @@
-use std::borrow::{Borrow, Cow};
+use std::borrow::Borrow;
 
 use crate::{
-	filter::{Filter, WhereFilter},
+	filter::{Aggregates, Cursor, Filter, OffsetPage, Page, WhereFilter},
 	row::{FromRowOwned, NamedColumns, ToRow, ToRowStatic},
-	Connection, Error,
+	Connection, Error, Ident,
 };
 
 #[derive(Debug, Clone)]
 pub struct Table {
-	name: Cow<'static, str>,
+	name: Ident,
 }
 
 impl Table {
-	pub fn new(name: impl Into<Cow<'static, str>>) -> Self {
+	pub fn new(name: impl Into<Ident>) -> Self {
 		Self { name: name.into() }
 	}
 
 	pub fn with_conn<'a>(&'a self, conn: Connection<'a>) -> TableWithConn<'a> {
-		TableWithConn { table: &self, conn }
+		TableWithConn { table: self, conn }
 	}
 }
 
@@ -30,7 +30,7 @@ pub struct TableWithConn<'a> {
 impl TableWithConn<'_> {
 	/// Get the name of the table
 	pub fn name(&self) -> &str {
-		self.table.name.as_ref()
+		self.table.name.as_str()
 	}
 
 	pub async fn select<R>(
@@ -40,7 +40,21 @@ impl TableWithConn<'_> {
 	where
 		R: FromRowOwned + NamedColumns,
 	{
-		self.conn.select(self.name(), filter).await
+		self.conn.select(&self.table.name, filter).await
+	}
+
+	pub async fn select_columns<R, C>(
+		&self,
+		columns: &[C],
+		filter: impl Borrow<Filter<'_>>,
+	) -> Result<Vec<R>, Error>
+	where
+		R: FromRowOwned,
+		C: AsRef<str>,
+	{
+		self.conn
+			.select_columns(&self.table.name, columns, filter)
+			.await
 	}
 
 	pub async fn select_one<R>(
@@ -50,7 +64,7 @@ impl TableWithConn<'_> {
 	where
 		R: FromRowOwned + NamedColumns,
 	{
-		self.conn.select_one(self.name(), filter).await
+		self.conn.select_one(&self.table.name, filter).await
 	}
 
 	pub async fn select_opt<R>(
@@ -60,22 +74,75 @@ impl TableWithConn<'_> {
 	where
 		R: FromRowOwned + NamedColumns,
 	{
-		self.conn.select_opt(self.name(), filter).await
+		self.conn.select_opt(&self.table.name, filter).await
+	}
+
+	pub async fn select_page<R>(
+		&self,
+		filter: impl Borrow<Filter<'_>>,
+		page: usize,
+		per_page: usize,
+	) -> Result<OffsetPage<R>, Error>
+	where
+		R: FromRowOwned + NamedColumns,
+	{
+		self.conn
+			.select_page(&self.table.name, filter, page, per_page)
+			.await
+	}
+
+	pub async fn select_keyset<'a, R>(
+		&self,
+		filter: Filter<'a>,
+		after: Option<&'a Cursor>,
+	) -> Result<Page<R>, Error>
+	where
+		R: FromRowOwned + NamedColumns,
+	{
+		self.conn
+			.select_keyset(&self.table.name, filter, after)
+			.await
 	}
 
 	pub async fn count(
 		&self,
-		column: &str,
+		column: impl Into<Ident>,
 		filter: impl Borrow<Filter<'_>>,
-	) -> Result<u32, Error> {
-		self.conn.count(self.name(), column, filter).await
+	) -> Result<u64, Error> {
+		self.conn.count(&self.table.name, column, filter).await
+	}
+
+	pub async fn aggregate<R>(
+		&self,
+		aggregates: &Aggregates<'_>,
+		filter: impl Borrow<Filter<'_>>,
+	) -> Result<Vec<R>, Error>
+	where
+		R: FromRowOwned,
+	{
+		self.conn
+			.aggregate(&self.table.name, aggregates, filter)
+			.await
+	}
+
+	pub async fn aggregate_one<R>(
+		&self,
+		aggregates: &Aggregates<'_>,
+		filter: impl Borrow<Filter<'_>>,
+	) -> Result<R, Error>
+	where
+		R: FromRowOwned,
+	{
+		self.conn
+			.aggregate_one(&self.table.name, aggregates, filter)
+			.await
 	}
 
 	pub async fn insert<U>(&self, item: &U) -> Result<(), Error>
 	where
 		U: ToRow,
 	{
-		self.conn.insert(self.name(), item).await
+		self.conn.insert(&self.table.name, item).await
 	}
 
 	pub async fn insert_many<U, I>(&self, items: I) -> Result<(), Error>
@@ -84,7 +151,7 @@ impl TableWithConn<'_> {
 		I: IntoIterator,
 		I::Item: Borrow<U>,
 	{
-		self.conn.insert_many(self.name(), items).await
+		self.conn.insert_many(&self.table.name, items).await
 	}
 
 	pub async fn update<U>(
@@ -95,13 +162,13 @@ impl TableWithConn<'_> {
 	where
 		U: ToRow,
 	{
-		self.conn.update(self.name(), item, filter).await
+		self.conn.update(&self.table.name, item, filter).await
 	}
 
 	pub async fn delete(
 		&self,
 		filter: impl Borrow<WhereFilter<'_>>,
 	) -> Result<(), Error> {
-		self.conn.delete(self.name(), filter).await
+		self.conn.delete(&self.table.name, filter).await
 	}
 }
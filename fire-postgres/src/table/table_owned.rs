@@ -2,14 +2,13 @@
 //!
 //! Might remove it in the future, let's see
 
-use super::util::info_data_to_sql;
 use super::{Info, TableTemplate};
 
 use crate::connection::ConnectionOwned;
 use crate::database::DatabaseError;
-use crate::filter::{Filter, WhereFilter};
+use crate::filter::{Filter, OffsetPage, WhereFilter};
 use crate::row::ToRow;
-use crate::{filter, Database, Error, Result};
+use crate::{filter, Database, Error, Ident, Result};
 
 use std::borrow::Borrow;
 use std::marker::PhantomData;
@@ -64,7 +63,7 @@ where
 
 	// Create
 	pub async fn try_create(&self) -> Result<()> {
-		let sql = info_data_to_sql(self.name, self.meta.info.data());
+		let sql = self.meta.info.create_sql(self.name);
 
 		self.get_connection()
 			.await?
@@ -140,11 +139,26 @@ where
 			.await
 	}
 
+	/// Returns a page of rows together with the number of all rows which
+	/// match the filter, the page starts at zero.
+	pub async fn find_page(
+		&self,
+		filter: impl Borrow<Filter<'_>>,
+		page: usize,
+		per_page: usize,
+	) -> Result<OffsetPage<T>> {
+		self.get_connection()
+			.await?
+			.connection()
+			.select_page(self.name, filter, page, per_page)
+			.await
+	}
+
 	pub async fn count<'a>(
 		&self,
-		column: &str,
+		column: impl Into<Ident>,
 		filter: impl Borrow<Filter<'_>>,
-	) -> Result<u32> {
+	) -> Result<u64> {
 		self.get_connection()
 			.await?
 			.connection()
@@ -1,8 +1,12 @@
 use super::column::{Column, IndexKind};
+use crate::ident::write_quoted;
+use crate::Ident;
 
 pub fn info_data_to_sql(name: &str, data: &[Column]) -> String {
 	let mut primary_indexes = vec![];
 	let mut normal_indexes = vec![];
+	let mut gin_indexes = vec![];
+	let mut trigram_indexes = vec![]; // (name, gist)
 	let mut unique_indexes = vec![]; // (name, vec![])
 
 	let mut cols_sql = vec![];
@@ -30,6 +34,9 @@ pub fn info_data_to_sql(name: &str, data: &[Column]) -> String {
 				break;
 			},
 			IndexKind::Index => normal_indexes.push(col.name),
+			IndexKind::Gin => gin_indexes.push(col.name),
+			IndexKind::Trigram => trigram_indexes.push((col.name, false)),
+			IndexKind::TrigramGist => trigram_indexes.push((col.name, true)),
 			IndexKind::None => {}
 		}
 	}
@@ -39,25 +46,74 @@ pub fn info_data_to_sql(name: &str, data: &[Column]) -> String {
 		cols_sql.push(format!("UNIQUE ({})", ind.1.join(", ")));
 	}
 
-	let mut sqls = vec![format!(
-		"CREATE TABLE IF NOT EXISTS \"{}\" ({})",
-		name,
+	let mut sqls = vec![];
+
+	// the trigram operator classes come from pg_trgm
+	if !trigram_indexes.is_empty() {
+		sqls.push("CREATE EXTENSION IF NOT EXISTS pg_trgm".to_string());
+	}
+
+	sqls.push(format!(
+		"CREATE TABLE IF NOT EXISTS {} ({})",
+		quote(name),
 		cols_sql.join(", ")
-	)];
+	));
 
 	for ind in normal_indexes {
-		let index_name = format!("{}_{}_nidx", name, ind);
+		let index_name = index_name(name, ind, "nidx");
+		sqls.push(format!(
+			"CREATE INDEX IF NOT EXISTS {} ON {} ({})",
+			index_name,
+			quote(name),
+			quote(ind)
+		));
+	}
+
+	for ind in gin_indexes {
+		let index_name = index_name(name, ind, "gidx");
 		sqls.push(format!(
-			"CREATE INDEX IF NOT EXISTS {} ON \"{}\" (\"{}\")",
-			index_name, name, ind
+			"CREATE INDEX IF NOT EXISTS {} ON {} USING GIN ({})",
+			index_name,
+			quote(name),
+			quote(ind)
+		));
+	}
+
+	for (ind, gist) in trigram_indexes {
+		let index_name = index_name(name, ind, "tidx");
+		let (method, ops) = if gist {
+			("GIST", "gist_trgm_ops")
+		} else {
+			("GIN", "gin_trgm_ops")
+		};
+		sqls.push(format!(
+			"CREATE INDEX IF NOT EXISTS {} ON {} USING {} ({} {})",
+			index_name,
+			quote(name),
+			method,
+			quote(ind),
+			ops
 		));
 	}
 
 	sqls.join("; ")
 }
 
+/// Returns the escaped name of an index on the column.
+///
+/// The name is lowercased like postgres does with unquoted names, indexes
+/// which were created before the names were quoted keep their name.
+fn index_name(table: &str, column: &str, suffix: &str) -> Ident {
+	format!("{}_{}_{}", table, column, suffix)
+		.to_ascii_lowercase()
+		.into()
+}
+
+/// Quotes and escapes a name.
 pub fn quote(s: &str) -> String {
-	format!("\"{}\"", s)
+	let mut quoted = String::with_capacity(s.len() + 2);
+	write_quoted(&mut quoted, s).unwrap();
+	quoted
 }
 
 // maybe this is not important because table name can't be -
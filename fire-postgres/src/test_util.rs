@@ -0,0 +1,17 @@
+//! Fixtures shared by the unit tests
+
+use crate::FromRow;
+
+#[derive(Debug, PartialEq, FromRow)]
+pub struct User {
+	pub id: i32,
+	pub name: String,
+	pub age: Option<i32>,
+}
+
+pub fn block_on<F: std::future::Future>(f: F) -> F::Output {
+	tokio::runtime::Builder::new_current_thread()
+		.build()
+		.unwrap()
+		.block_on(f)
+}
@@ -0,0 +1,212 @@
+//! Ephemeral databases for tests
+//!
+//! Every [`TestDatabase`] creates its own uniquely named database, so tests
+//! can run in parallel without interfering with each other. The database can
+//! be created from a template which already has all migrations applied.
+//!
+//! The easiest way to use it is with the [`test`](crate::test) attribute,
+//! which reads the admin connection url from the environment variable
+//! `FIRE_POSTGRES_TEST_URL`.
+//!
+//! Needs postgres 13 or newer, the databases are dropped with
+//! `DROP DATABASE ... WITH (FORCE)`.
+//!
+//! ## Example
+//! ```no_run
+//! use fire_postgres::Database;
+//!
+//! #[fire_postgres::test]
+//! async fn insert_user(db: Database) {
+//! 	let conn = db.get().await.unwrap();
+//! 	conn.connection()
+//! 		.batch_execute("CREATE TABLE users (id int4)")
+//! 		.await
+//! 		.unwrap();
+//! }
+//!
+//! #[fire_postgres::test(template = "app_template")]
+//! async fn with_migrations(db: Database) {
+//! 	// all tables from app_template already exist
+//! }
+//! ```
+
+use std::env;
+use std::fmt::Write;
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+
+use futures_util::FutureExt;
+use rand::Rng;
+use tokio_postgres::NoTls;
+use tracing::error;
+
+use crate::connection::ConnectionOwned;
+use crate::database::{Config, DatabaseError};
+use crate::{Database, Error, Ident};
+
+/// The environment variable the [`test`](crate::test) attribute reads the
+/// admin connection url from.
+pub const URL_ENV: &str = "FIRE_POSTGRES_TEST_URL";
+
+/// A database which only exists for the duration of a test.
+///
+/// Call [`TestDatabase::cleanup`] to drop the database, since dropping
+/// requires a connection this does not happen automatically.
+#[derive(Debug)]
+pub struct TestDatabase {
+	admin: Config,
+	name: String,
+	db: Database,
+}
+
+impl TestDatabase {
+	/// Creates a new empty database.
+	///
+	/// The admin config needs to point to an existing database (for example
+	/// `postgres`) with a user which is allowed to create databases.
+	pub async fn new(admin: Config) -> Result<Self, DatabaseError> {
+		Self::create(admin, None).await
+	}
+
+	/// Creates a new database which is a copy of the template.
+	///
+	/// Postgres does not allow other connections to the template while the
+	/// copy is created.
+	pub async fn from_template(
+		admin: Config,
+		template: &str,
+	) -> Result<Self, DatabaseError> {
+		Self::create(admin, Some(template)).await
+	}
+
+	async fn create(
+		admin: Config,
+		template: Option<&str>,
+	) -> Result<Self, DatabaseError> {
+		let name = format!("test_{:016x}", rand::thread_rng().gen::<u64>());
+
+		let mut sql = format!("CREATE DATABASE {}", Ident::from(name.clone()));
+		if let Some(template) = template {
+			write!(sql, " TEMPLATE {}", Ident::from(template.to_string()))
+				.unwrap();
+		}
+
+		admin_connection(&admin)
+			.await?
+			.connection()
+			.batch_execute(&sql)
+			.await?;
+
+		let mut cfg = admin.clone();
+		cfg.dbname = Some(name.clone());
+
+		let db = match Database::with_cfg(cfg).await {
+			Ok(db) => db,
+			Err(e) => {
+				// don't leave the database behind
+				let _ = drop_database(&admin, &name).await;
+				return Err(e);
+			}
+		};
+
+		Ok(Self { admin, name, db })
+	}
+
+	/// The name of the created database
+	pub fn name(&self) -> &str {
+		&self.name
+	}
+
+	pub fn db(&self) -> &Database {
+		&self.db
+	}
+
+	/// Closes all connections and drops the database.
+	///
+	/// Uses `DROP DATABASE ... WITH (FORCE)` which needs postgres 13 or newer,
+	/// so connections which are still open don't prevent the drop.
+	pub async fn cleanup(self) -> Result<(), DatabaseError> {
+		self.db.close();
+
+		drop_database(&self.admin, &self.name).await
+	}
+}
+
+/// Runs a test with a new database, used by the [`test`](crate::test)
+/// attribute.
+///
+/// The database is dropped even if the test panics.
+///
+/// ## Panics
+/// If `FIRE_POSTGRES_TEST_URL` is not set or the database could not be
+/// created.
+pub fn run<F, Fut, R>(template: Option<&str>, test: F) -> R
+where
+	F: FnOnce(Database) -> Fut,
+	Fut: Future<Output = R>,
+{
+	let url = env::var(URL_ENV)
+		.unwrap_or_else(|_| panic!("environment variable {URL_ENV} not set"));
+	let admin = Config {
+		url: Some(url),
+		..Default::default()
+	};
+
+	let rt = tokio::runtime::Builder::new_current_thread()
+		.enable_all()
+		.build()
+		.expect("could not build runtime");
+
+	rt.block_on(async move {
+		let test_db = match template {
+			Some(template) => {
+				TestDatabase::from_template(admin, template).await
+			}
+			None => TestDatabase::new(admin).await,
+		}
+		.expect("could not create test database");
+
+		let res = AssertUnwindSafe(test(test_db.db().clone()))
+			.catch_unwind()
+			.await;
+
+		let name = test_db.name().to_string();
+		if let Err(e) = test_db.cleanup().await {
+			error!("could not drop test database {name} {e}");
+		}
+
+		res.unwrap_or_else(|e| panic::resume_unwind(e))
+	})
+}
+
+async fn admin_connection(
+	admin: &Config,
+) -> Result<ConnectionOwned, DatabaseError> {
+	let cfg = admin.get_pg_config().map_err(DatabaseError::Config)?;
+	let (client, connection) = cfg.connect(NoTls).await?;
+
+	tokio::spawn(async move {
+		if let Err(e) = connection.await {
+			error!("connection error {e}");
+		}
+	});
+
+	Ok(ConnectionOwned::from_client(client))
+}
+
+async fn drop_database(
+	admin: &Config,
+	name: &str,
+) -> Result<(), DatabaseError> {
+	let sql = format!(
+		"DROP DATABASE IF EXISTS {} WITH (FORCE)",
+		Ident::from(name.to_string())
+	);
+
+	admin_connection(admin)
+		.await?
+		.connection()
+		.batch_execute(&sql)
+		.await
+		.map_err(Error::into)
+}
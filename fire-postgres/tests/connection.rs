@@ -0,0 +1,55 @@
+//! Those tests need a running postgres server, run them with
+//! `FIRE_POSTGRES_TEST_URL="host=localhost user=postgres" cargo test -- --ignored`
+
+use std::env;
+use std::thread;
+
+use fire_postgres::connection::ConnectionOwned;
+use fire_postgres::testing::URL_ENV;
+use fire_postgres::{Database, Row};
+use tokio_postgres::NoTls;
+
+#[fire_postgres::test]
+#[ignore = "needs a postgres server"]
+async fn from_client_caches_statements(_db: Database) {
+	let url = env::var(URL_ENV).unwrap();
+	let (client, connection) =
+		tokio_postgres::connect(&url, NoTls).await.unwrap();
+	tokio::spawn(connection);
+
+	// does not need a runtime
+	let mut owned = thread::spawn(move || ConnectionOwned::from_client(client))
+		.join()
+		.unwrap();
+
+	let conn = owned.connection();
+	let first = conn.prepare_cached("SELECT 1").await.unwrap();
+	let second = conn.prepare_cached("SELECT 1").await.unwrap();
+	assert_eq!(prepared(&owned).await, 1);
+
+	// transactions share the cache of the client
+	let tr = owned.transaction().await.unwrap();
+	let in_tr = tr.connection().prepare_cached("SELECT 1").await.unwrap();
+	tr.commit().await.unwrap();
+	assert_eq!(prepared(&owned).await, 1);
+
+	let uncached = owned.connection().prepare("SELECT 1").await.unwrap();
+	assert_eq!(prepared(&owned).await, 2);
+
+	drop((first, second, in_tr, uncached));
+}
+
+/// Returns how many statements `SELECT 1` are prepared in the session.
+async fn prepared(conn: &ConnectionOwned) -> i64 {
+	let row = conn
+		.connection()
+		.query_one::<Row, _>(
+			"SELECT count(*) FROM pg_prepared_statements \
+			WHERE statement = 'SELECT 1'",
+			&[],
+		)
+		.await
+		.unwrap();
+
+	row.get(0)
+}
@@ -2,9 +2,12 @@ use fire_postgres::enum_u16;
 use fire_postgres::row;
 use fire_postgres::row::NamedColumns;
 use fire_postgres::row::ToRowStatic;
+use fire_postgres::table::column::{Column, IndexKind};
+use fire_postgres::table::TableTemplate;
 use fire_postgres::{FromRow, TableTempl, ToRow, UniqueId};
 
 #[derive(Debug, TableTempl, FromRow, ToRow)]
+#[row(columns)]
 pub struct Table {
 	#[index(primary)]
 	pub id: UniqueId,
@@ -13,10 +16,54 @@ pub struct Table {
 	pub ty: Type,
 }
 
+#[derive(Debug, TableTempl, FromRow, ToRow)]
+#[text_search(name = "search", language = "english", columns(title, body))]
+#[row(columns)]
+pub struct Post {
+	#[index(primary)]
+	pub id: UniqueId,
+	pub title: String,
+	pub body: String,
+}
+
+// without a language the simple configuration is used
+#[derive(Debug, TableTempl, FromRow, ToRow)]
+#[text_search(name = "search", columns(title))]
+pub struct Note {
+	#[index(primary)]
+	pub id: UniqueId,
+	pub title: String,
+}
+
+// without `#[row(columns)]` the names of the constants are free
+impl Note {
+	pub const TITLE: &'static str = "Note";
+	pub const SEARCH: usize = 1;
+}
+
+#[derive(Debug, TableTempl, FromRow, ToRow)]
+pub struct Tagged {
+	#[index(primary)]
+	pub id: UniqueId,
+	#[index(trigram)]
+	pub name: String,
+	#[index(trigram_gist)]
+	pub nick: String,
+	#[index(gin)]
+	pub tags: Vec<String>,
+}
+
 #[derive(Debug, FromRow)]
 #[allow(dead_code)]
 pub struct Count(u32);
 
+#[derive(Debug, FromRow)]
+#[allow(dead_code)]
+#[row(columns)]
+pub struct Raw {
+	pub r#type: String,
+}
+
 enum_u16! {
 	#[derive(Debug)]
 	pub enum Type {
@@ -45,3 +92,159 @@ fn test_create_row() {
 		s
 	};
 }
+
+#[test]
+fn test_text_search_column() {
+	let info = Post::table_info();
+	assert_eq!(
+		info.data().last().unwrap(),
+		&Column::text_search("search", "english", vec!["title", "body"])
+	);
+	assert_eq!(Post::select_columns(), r#""id", "title", "body""#);
+
+	assert_eq!(
+		Note::table_info().data().last().unwrap(),
+		&Column::text_search("search", "simple", vec!["title"])
+	);
+	assert_eq!((Note::TITLE, Note::SEARCH), ("Note", 1));
+}
+
+#[test]
+fn test_text_search_create_sql() {
+	let sql = Post::table_info().create_sql("posts");
+	assert_eq!(
+		sql,
+		"CREATE TABLE IF NOT EXISTS \"posts\" (\
+		\"id\" text CHECK (length(id)=14) not null, \
+		\"title\" text not null, \
+		\"body\" text not null, \
+		\"search\" tsvector GENERATED ALWAYS AS (to_tsvector('english', \
+		coalesce(\"title\", '') || ' ' || coalesce(\"body\", ''))) STORED \
+		not null, \
+		PRIMARY KEY (\"id\")); \
+		CREATE INDEX IF NOT EXISTS \"posts_search_gidx\" ON \"posts\" \
+		USING GIN (\"search\")"
+	);
+}
+
+#[test]
+fn test_index_create_sql() {
+	let info = Tagged::table_info();
+	let indexes: Vec<_> = info.data().iter().map(|c| &c.index).collect();
+	assert_eq!(
+		indexes,
+		[
+			&IndexKind::Primary,
+			&IndexKind::Trigram,
+			&IndexKind::TrigramGist,
+			&IndexKind::Gin
+		]
+	);
+
+	let sql = info.create_sql("tagged");
+	let stmts: Vec<_> = sql.split("; ").collect();
+	assert_eq!(stmts[0], "CREATE EXTENSION IF NOT EXISTS pg_trgm");
+	assert!(stmts[1].starts_with("CREATE TABLE IF NOT EXISTS \"tagged\""));
+	assert_eq!(
+		stmts[2..],
+		[
+			"CREATE INDEX IF NOT EXISTS \"tagged_tags_gidx\" ON \"tagged\" \
+			USING GIN (\"tags\")",
+			"CREATE INDEX IF NOT EXISTS \"tagged_name_tidx\" ON \"tagged\" \
+			USING GIN (\"name\" gin_trgm_ops)",
+			"CREATE INDEX IF NOT EXISTS \"tagged_nick_tidx\" ON \"tagged\" \
+			USING GIST (\"nick\" gist_trgm_ops)",
+		]
+	);
+
+	// the names of the indexes are escaped as well
+	let sql = info.create_sql(r#"Tag"ged"#);
+	assert!(sql.contains(
+		r#"CREATE INDEX IF NOT EXISTS "tag""ged_name_tidx" ON "Tag""ged""#
+	));
+
+	// without a trigram index the extension is not needed
+	let sql = Post::table_info().create_sql("posts");
+	assert!(!sql.contains("pg_trgm"));
+}
+
+#[test]
+fn test_typed_columns() {
+	use fire_postgres::filter;
+	use fire_postgres::filter::Filter;
+
+	assert_eq!(Table::NAME.name(), "name");
+
+	let name = "Alice";
+	let age = 30;
+	let query = filter!(
+		Table::NAME = &name AND Table::AGE >= &age ORDER Table::AGE DESC
+	);
+	assert_eq!(
+		query.to_string(),
+		r#" WHERE "name" = $1 AND "age" >= $2 ORDER BY "age" DESC"#
+	);
+
+	let query = filter!(ORDER Table::ID ASC LIMIT 10);
+	assert_eq!(query.to_string(), r#" ORDER BY "id" ASC LIMIT 10"#);
+
+	let query = Filter::new()
+		.and_eq(Table::NAME, &name)
+		.or_gt(Table::AGE, &age)
+		.order_desc(Table::AGE);
+	assert_eq!(
+		query.to_string(),
+		r#" WHERE "name" = $1 OR "age" > $2 ORDER BY "age" DESC"#
+	);
+
+	let ages = vec![30, 31];
+	let names = vec!["Alice", "Bob"];
+	let query = filter!(Table::AGE IN &ages AND Table::NAME = ANY &names);
+	assert_eq!(
+		query.to_string(),
+		r#" WHERE "age" = ANY($1) AND "name" = ANY($2)"#
+	);
+
+	let query = Filter::new()
+		.and_in(Table::AGE, &ages)
+		.and_ne_all(Table::NAME, &names);
+	assert_eq!(
+		query.to_string(),
+		r#" WHERE "age" = ANY($1) AND "name" != ALL($2)"#
+	);
+
+	// the text search column comes from TableTempl
+	let search = "rust";
+	let query = filter!(Post::SEARCH @@ &search);
+	assert_eq!(
+		query.to_string(),
+		r#" WHERE "search" @@ websearch_to_tsquery($1)"#
+	);
+
+	// the column keeps the r# prefix like select_columns
+	assert_eq!(Raw::TYPE.name(), "r#type");
+	assert_eq!(Raw::select_columns(), r#""r#type""#);
+}
+
+#[test]
+fn test_filter_parser() {
+	use fire_postgres::filter::FilterParser;
+
+	let parser = FilterParser::from_table::<Table>().only(&["name", "age"]);
+	let filter = parser
+		.parse_query("name=Alice&age_gt=30&sort=-age")
+		.unwrap();
+	assert_eq!(
+		filter.to_string(),
+		r#" WHERE "name" = $1 AND "age" > $2 ORDER BY "age" DESC"#
+	);
+	assert!(parser.parse_query("id=1").is_err());
+
+	let filter = FilterParser::from_table::<Post>()
+		.parse_query("search_search=rust+postgres")
+		.unwrap();
+	assert_eq!(
+		filter.to_string(),
+		r#" WHERE "search" @@ websearch_to_tsquery($1)"#
+	);
+}